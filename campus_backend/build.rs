@@ -1,6 +1,10 @@
+use std::path::PathBuf;
+
 use prost_build::Config;
 use protoc_bin_vendored::protoc_bin_path;
 
+const PROTOS: [&str; 2] = ["proto/common.proto", "proto/course.proto"];
+
 fn main() {
     // 获取自动内置的 protoc 路径
     let protoc_path = protoc_bin_path().expect("Failed to get protoc binary");
@@ -8,14 +12,26 @@ fn main() {
     // 设置 PROTOC 环境变量 —— 告诉 prost-build 使用内置 protoc
     std::env::set_var("PROTOC", protoc_path);
 
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR not set"));
+    let descriptor_path = out_dir.join("proto_descriptor.bin");
+
     let mut config = Config::new();
 
     config
-        .compile_protos(
-            &["proto/course.proto"],
-            &["proto"],
-        )
+        .file_descriptor_set_path(&descriptor_path)
+        .compile_protos(&PROTOS, &["proto"])
         .expect("Failed to compile protobuf files");
 
-    println!("cargo:rerun-if-changed=proto/course.proto");
+    // 为所有消息生成 proto3 JSON 映射的 serde 实现（同时用于 JSON 请求体和 Query 参数）
+    let descriptors = std::fs::read(&descriptor_path).expect("Failed to read descriptor set");
+    pbjson_build::Builder::new()
+        .register_descriptors(&descriptors)
+        .expect("Failed to register descriptors")
+        .ignore_unknown_fields()
+        .build(&[".common", ".course"])
+        .expect("Failed to generate serde implementations");
+
+    for proto in PROTOS {
+        println!("cargo:rerun-if-changed={}", proto);
+    }
 }
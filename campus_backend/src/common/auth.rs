@@ -1,7 +1,10 @@
 // src/common/auth.rs
+use std::marker::PhantomData;
+use std::ops::Deref;
+
 use axum::{
     async_trait,
-    extract::{FromRequestParts, State}, // 引入 State
+    extract::FromRequestParts,
     http::request::Parts,
 };
 use axum_extra::{
@@ -12,26 +15,48 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use serde::{Deserialize, Serialize};
 
 use crate::common::error::AppError;
+use crate::common::telemetry;
 use crate::common::state::AppState; // 引入刚才定义的 State
 
 // ==========================================
 // 1. 定义数据结构 (增加了 role)
 // ==========================================
 
+/// Token 类型：短期 access token / 长期 refresh token
+pub const TOKEN_TYPE_ACCESS: &str = "access";
+pub const TOKEN_TYPE_REFRESH: &str = "refresh";
+
 // JWT 里的载荷
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
-    pub user_id: i64,
+    pub user_id: String, // users.id (UUID)
     pub role: String,   // ✨ 新增：角色字段 (student/admin)
     pub exp: usize,
     pub iat: usize,
+    pub jti: String,    // Token 唯一 ID，退出登录时写入吊销列表
+    pub typ: String,    // access / refresh
+    pub fam: String,    // Token 家族 ID：同一次登录轮换出来的 Token 共享
 }
 
 // Controller 里直接拿到的用户信息对象
 #[derive(Debug, Clone)]
 pub struct AuthUser {
-    pub user_id: i64,
+    pub user_id: String,
     pub role: String,   // ✨ 新增：让 Controller 也能直接读取角色
+    pub jti: String,
+    pub exp: i64,       // Token 过期时间（Unix 秒）
+    pub family: String,
+}
+
+/// 登录 / 刷新时返回给客户端的一对 Token
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// access token 有效期（秒）
+    pub expires_in: i64,
+    /// refresh token 有效期（秒）
+    pub refresh_expires_in: i64,
 }
 
 // ==========================================
@@ -57,45 +82,346 @@ where
         let config = &state.jwt_config;
 
         // 3. 解码验证
-        let token_data = decode::<Claims>(
-            bearer.token(),
-            &DecodingKey::from_secret(config.secret.as_bytes()),
-            &Validation::default(),
-        )
-            .map_err(|e| match e.kind() {
-                jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
-                    AppError::Unauthorized("Token 已过期 (Expired)".to_string())
-                }
-                _ => AppError::Unauthorized("Token 无效 (Invalid)".to_string()),
-            })?;
-
-        // 4. 返回封装好的用户对象
+        let claims = decode_claims(bearer.token(), &config.secret)?;
+
+        // refresh token 只能用来换新 Token，不能直接访问接口
+        if claims.typ != TOKEN_TYPE_ACCESS {
+            return Err(AppError::Unauthorized("Token 类型错误 (Wrong Token Type)".to_string()));
+        }
+
+        // 4. 检查是否已经退出登录（吊销列表）
+        if state.revocation.is_revoked(&claims.jti).await?
+            || state.revocation.is_family_revoked(&claims.fam).await?
+        {
+            return Err(AppError::Unauthorized("Token 已注销 (Revoked)".to_string()));
+        }
+
+        // 访问日志里记录是谁发起的请求
+        telemetry::record_user(&claims.user_id);
+
+        // 5. 返回封装好的用户对象
         Ok(AuthUser {
-            user_id: token_data.claims.user_id,
-            role: token_data.claims.role, // 传递角色
+            user_id: claims.user_id,
+            role: claims.role, // 传递角色
+            jti: claims.jti,
+            exp: claims.exp as i64,
+            family: claims.fam,
         })
     }
 }
 
+/// 可选登录：没有带 Authorization 头时为 None（匿名访问）
+/// 带了头但 Token 无效/过期/已注销时仍然返回 401，方便客户端及时刷新 Token
+#[derive(Debug, Clone)]
+pub struct OptionalAuthUser(pub Option<AuthUser>);
+
+impl OptionalAuthUser {
+    /// 当前用户 ID（匿名时为 None）
+    pub fn user_id(&self) -> Option<&str> {
+        self.0.as_ref().map(|user| user.user_id.as_str())
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for OptionalAuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key(axum::http::header::AUTHORIZATION) {
+            return Ok(OptionalAuthUser(None));
+        }
+
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        Ok(OptionalAuthUser(Some(user)))
+    }
+}
+
 // ==========================================
-// 3. 辅助函数 (生成 Token)
+// 3. 角色校验提取器 (RequireRole<Admin> 等)
 // ==========================================
 
-/// 生成 Token 的通用函数
-/// 注意：现在需要传入 role
-pub fn generate_token(user_id: i64, role: &str, secret: &str, expiration_seconds: i64) -> Result<String, AppError> {
+/// 角色标记，配合 `RequireRole<R>` 在 Handler 签名里声明所需角色
+pub trait Role: Send + Sync + 'static {
+    const NAME: &'static str;
+
+    /// 当前用户角色是否满足要求（管理员拥有所有角色的权限）
+    fn allows(role: &str) -> bool {
+        role == Self::NAME || role == Admin::NAME
+    }
+}
+
+/// 管理员
+pub struct Admin;
+
+impl Role for Admin {
+    const NAME: &'static str = "admin";
+}
+
+/// 教师
+pub struct Teacher;
+
+impl Role for Teacher {
+    const NAME: &'static str = "teacher";
+}
+
+/// 要求当前用户具备角色 `R`，否则返回 403
+/// 用法：`async fn handler(admin: RequireRole<Admin>) { admin.user_id ... }`
+pub struct RequireRole<R: Role> {
+    pub user: AuthUser,
+    _role: PhantomData<fn() -> R>,
+}
+
+impl<R: Role> Deref for RequireRole<R> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.user
+    }
+}
+
+#[async_trait]
+impl<R: Role> FromRequestParts<AppState> for RequireRole<R> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+
+        if !R::allows(&user.role) {
+            return Err(AppError::Forbidden(format!(
+                "权限不足，需要 {} 角色 (Forbidden)",
+                R::NAME
+            )));
+        }
+
+        Ok(RequireRole {
+            user,
+            _role: PhantomData,
+        })
+    }
+}
+
+// ==========================================
+// 4. 辅助函数 (生成 Token)
+// ==========================================
+
+/// 解码并校验签名和过期时间
+fn decode_claims(token: &str, secret: &str) -> Result<Claims, AppError> {
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )
+        .map(|data| data.claims)
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
+                AppError::Unauthorized("Token 已过期 (Expired)".to_string())
+            }
+            _ => AppError::Unauthorized("Token 无效 (Invalid)".to_string()),
+        })
+}
+
+fn encode_claims(
+    user_id: &str,
+    role: &str,
+    typ: &str,
+    family: &str,
+    secret: &str,
+    expiration_seconds: i64,
+) -> Result<(String, Claims), AppError> {
     let now = chrono::Utc::now().timestamp() as usize;
     let claims = Claims {
-        user_id,
+        user_id: user_id.to_owned(),
         role: role.to_owned(), // 写入角色
         exp: now + expiration_seconds as usize,
         iat: now,
+        jti: uuid::Uuid::new_v4().to_string(),
+        typ: typ.to_owned(),
+        fam: family.to_owned(),
     };
 
-    encode(
+    let token = encode(
         &Header::default(),
         &claims,
         &EncodingKey::from_secret(secret.as_bytes()),
     )
-        .map_err(|e| AppError::InternalError(format!("Token creation failed: {}", e)))
-}
\ No newline at end of file
+        .map_err(|e| AppError::InternalError(format!("Token creation failed: {}", e)))?;
+
+    Ok((token, claims))
+}
+
+/// 生成 Token 的通用函数
+/// 注意：现在需要传入 role
+/// 只生成单独的 access token（不带 refresh token），主要给开发工具使用
+pub fn generate_token(user_id: &str, role: &str, secret: &str, expiration_seconds: i64) -> Result<String, AppError> {
+    let family = uuid::Uuid::new_v4().to_string();
+    encode_claims(user_id, role, TOKEN_TYPE_ACCESS, &family, secret, expiration_seconds)
+        .map(|(token, _)| token)
+}
+
+/// 在指定家族下签发 access + refresh，并把 refresh 的 jti 记为家族当前有效的那一个
+async fn sign_pair(
+    state: &AppState,
+    user_id: &str,
+    role: &str,
+    family: &str,
+    previous_refresh_jti: Option<&str>,
+) -> Result<TokenPair, AppError> {
+    let config = &state.jwt_config;
+
+    let (access_token, _) = encode_claims(
+        user_id, role, TOKEN_TYPE_ACCESS, family, &config.secret, config.expiration,
+    )?;
+    let (refresh_token, refresh_claims) = encode_claims(
+        user_id, role, TOKEN_TYPE_REFRESH, family, &config.secret, config.refresh_expiration,
+    )?;
+    let refresh_exp = refresh_claims.exp as i64;
+
+    match previous_refresh_jti {
+        None => {
+            state
+                .revocation
+                .set_refresh_head(family, &refresh_claims.jti, refresh_exp)
+                .await?;
+        }
+        Some(previous) => {
+            let rotated = state
+                .revocation
+                .rotate_refresh_head(family, previous, &refresh_claims.jti, refresh_exp)
+                .await?;
+
+            if !rotated {
+                // 旧的 refresh token 被再次使用：说明它可能已经泄露，整个家族一起作废
+                tracing::warn!("Refresh token reuse detected, revoking family {}", family);
+                state.revocation.revoke_family(family, refresh_exp).await?;
+                return Err(AppError::Unauthorized(
+                    "Refresh Token 已被使用，请重新登录 (Reused)".to_string(),
+                ));
+            }
+        }
+    }
+
+    state
+        .revocation
+        .track_user_family(user_id, family, refresh_exp)
+        .await?;
+
+    Ok(TokenPair {
+        access_token,
+        refresh_token,
+        expires_in: config.expiration,
+        refresh_expires_in: config.refresh_expiration,
+    })
+}
+
+/// 登录成功后签发一对新的 Token（开启一个新的 Token 家族）
+pub async fn issue_token_pair(state: &AppState, user_id: &str, role: &str) -> Result<TokenPair, AppError> {
+    let family = uuid::Uuid::new_v4().to_string();
+    sign_pair(state, user_id, role, &family, None).await
+}
+
+/// 用 refresh token 换一对新 Token，旧的 refresh token 随即失效（轮换）
+/// 角色以数据库为准：刷新期间被调整角色的用户拿到的是新角色，已删除的用户不能再刷新
+pub async fn refresh_token_pair(state: &AppState, refresh_token: &str) -> Result<TokenPair, AppError> {
+    let claims = verify_refresh_token(state, refresh_token).await?;
+
+    let role: Option<String> = sqlx::query_scalar("SELECT role FROM users WHERE id = ?")
+        .bind(&claims.user_id)
+        .fetch_optional(&state.pool)
+        .await?;
+
+    let Some(role) = role else {
+        state.revocation.revoke_family(&claims.fam, claims.exp as i64).await?;
+        return Err(AppError::Unauthorized("用户不存在 (User Not Found)".to_string()));
+    };
+
+    sign_pair(state, &claims.user_id, &role, &claims.fam, Some(&claims.jti)).await
+}
+
+/// 校验 refresh token 本身：签名、类型、所在家族未被吊销
+async fn verify_refresh_token(state: &AppState, refresh_token: &str) -> Result<Claims, AppError> {
+    let claims = decode_claims(refresh_token, &state.jwt_config.secret)?;
+
+    if claims.typ != TOKEN_TYPE_REFRESH {
+        return Err(AppError::Unauthorized("Token 类型错误 (Wrong Token Type)".to_string()));
+    }
+
+    if state.revocation.is_family_revoked(&claims.fam).await? {
+        return Err(AppError::Unauthorized("Token 已注销 (Revoked)".to_string()));
+    }
+
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 与 refresh_token_pair 相同，只是角色沿用 Token 里的值，不查数据库
+    async fn rotate(state: &AppState, refresh_token: &str) -> Result<TokenPair, AppError> {
+        let claims = verify_refresh_token(state, refresh_token).await?;
+        sign_pair(state, &claims.user_id, &claims.role, &claims.fam, Some(&claims.jti)).await
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotates_token() {
+        let state = AppState::for_tests();
+        let first = issue_token_pair(&state, "u1", "student").await.unwrap();
+
+        let second = rotate(&state, &first.refresh_token).await.unwrap();
+        assert_ne!(first.refresh_token, second.refresh_token);
+
+        // 新的 refresh token 可以继续轮换
+        rotate(&state, &second.refresh_token).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reuse_revokes_family() {
+        let state = AppState::for_tests();
+        let first = issue_token_pair(&state, "u1", "student").await.unwrap();
+        let second = rotate(&state, &first.refresh_token).await.unwrap();
+
+        // 旧 refresh token 再次出现 -> 拒绝
+        assert!(rotate(&state, &first.refresh_token).await.is_err());
+
+        // 整个家族被吊销，连合法的新 refresh token 也失效
+        assert!(rotate(&state, &second.refresh_token).await.is_err());
+
+        let claims = decode_claims(&second.access_token, "test-secret").unwrap();
+        assert!(state.revocation.is_family_revoked(&claims.fam).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_user_families() {
+        let state = AppState::for_tests();
+        let phone = issue_token_pair(&state, "u1", "student").await.unwrap();
+        let laptop = issue_token_pair(&state, "u1", "student").await.unwrap();
+        let other = issue_token_pair(&state, "u2", "student").await.unwrap();
+        let laptop = rotate(&state, &laptop.refresh_token).await.unwrap();
+
+        // 修改密码后 u1 的所有设备都要重新登录
+        state.revocation.revoke_user_families("u1").await.unwrap();
+
+        assert!(rotate(&state, &phone.refresh_token).await.is_err());
+        assert!(rotate(&state, &laptop.refresh_token).await.is_err());
+        rotate(&state, &other.refresh_token).await.unwrap();
+    }
+
+    #[test]
+    fn test_role_allows() {
+        assert!(Admin::allows("admin"));
+        assert!(!Admin::allows("teacher"));
+        assert!(!Admin::allows("student"));
+
+        assert!(Teacher::allows("teacher"));
+        assert!(Teacher::allows("admin"));
+        assert!(!Teacher::allows("student"));
+    }
+
+    #[tokio::test]
+    async fn test_access_token_cannot_refresh() {
+        let state = AppState::for_tests();
+        let pair = issue_token_pair(&state, "u1", "student").await.unwrap();
+
+        assert!(refresh_token_pair(&state, &pair.access_token).await.is_err());
+    }
+}
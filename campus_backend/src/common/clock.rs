@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+
+/// 可注入的时钟：定时任务等依赖“当前时间”的逻辑通过它取时间，测试时可以替换
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// 系统时钟（生产环境使用）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// 固定时钟（测试使用）
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -7,9 +7,9 @@ pub fn print_test_tokens() -> Result<(), AppError> {
     let config = JwtConfig::from_env();
 
     let test_users = vec![
-        (1, "admin", "admin"),      // ID, 用户名, 角色
-        (2, "student_a", "student"),
-        (3, "teacher_b", "teacher"),
+        ("1", "admin", "admin"),      // ID, 用户名, 角色
+        ("2", "student_a", "student"),
+        ("3", "teacher_b", "teacher"),
     ];
 
     println!("\n====== 🛠️ 开发测试 Token (有效期 {}秒) ======", config.expiration);
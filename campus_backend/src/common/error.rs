@@ -1,9 +1,13 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue},
     response::{IntoResponse, Response},
 };
+use sqlx::error::ErrorKind;
+use sqlx::mysql::MySqlDatabaseError;
 use std::fmt;
 
+use crate::common::response::{ErrorCode, ErrorEnvelope, FieldError};
+
 /// 应用统一错误类型
 #[derive(Debug)]
 pub enum AppError {
@@ -13,6 +17,8 @@ pub enum AppError {
     NotFound(String),
     /// 请求参数错误
     BadRequest(String),
+    /// 参数校验失败，带具体字段
+    ValidationFailed(Vec<FieldError>),
     /// 未授权 (Token 错误)
     Unauthorized(String),
 
@@ -20,11 +26,41 @@ pub enum AppError {
     /// 禁止访问 (权限不足)
     Forbidden(String),
 
+    /// 与现有数据冲突（重复创建、时间冲突、当前状态不允许等）
+    Conflict(String),
+    /// 请求过于频繁
+    TooManyRequests(String),
+
     /// 内部服务器错误
     #[allow(dead_code)]
     InternalError(String),
     /// Protobuf 解析错误
     ProtobufError(prost::DecodeError),
+    /// 请求体无法解析（JSON 格式错误等）
+    MalformedBody(String),
+}
+
+impl AppError {
+    /// 单个字段校验失败
+    pub fn invalid_field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::ValidationFailed(vec![FieldError::new(field, message)])
+    }
+
+    /// 对应的错误码（HTTP 状态码由错误码决定）
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::DatabaseError(e) => classify_db_error(e).0,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::ValidationFailed(_) => ErrorCode::ValidationFailed,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::TooManyRequests(_) => ErrorCode::TooManyRequests,
+            AppError::InternalError(_) => ErrorCode::Internal,
+            AppError::ProtobufError(_) | AppError::MalformedBody(_) => ErrorCode::MalformedBody,
+        }
+    }
 }
 
 impl fmt::Display for AppError {
@@ -33,10 +69,14 @@ impl fmt::Display for AppError {
             AppError::DatabaseError(e) => write!(f, "Database error: {}", e),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
+            AppError::ValidationFailed(details) => write!(f, "Validation failed: {:?}", details),
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
             AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg), // ✨ 对应的格式化
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "Too many requests: {}", msg),
             AppError::InternalError(msg) => write!(f, "Internal error: {}", msg),
             AppError::ProtobufError(e) => write!(f, "Protobuf error: {}", e),
+            AppError::MalformedBody(msg) => write!(f, "Malformed body: {}", msg),
         }
     }
 }
@@ -57,32 +97,168 @@ impl From<prost::DecodeError> for AppError {
     }
 }
 
-/// 实现 IntoResponse
+/// MySQL：要删除 / 修改的父行仍被外键引用（ER_ROW_IS_REFERENCED、ER_ROW_IS_REFERENCED_2、ER_FK_CANNOT_DELETE_PARENT）
+const MYSQL_ROW_IS_REFERENCED: [u16; 3] = [1217, 1451, 1834];
+
+/// 连接池取不到连接时，建议客户端多久后重试（秒）
+const POOL_RETRY_AFTER_SECS: u64 = 3;
+
+/// 数据库错误归类：唯一键冲突、外键失败、连接池超时等给出对应的错误码和对外提示，
+/// 其余仍是笼统的「数据库错误」（具体原因只写日志）
+fn classify_db_error(err: &sqlx::Error) -> (ErrorCode, &'static str) {
+    match err {
+        sqlx::Error::RowNotFound => (ErrorCode::NotFound, "数据不存在"),
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => {
+            (ErrorCode::ServiceUnavailable, "服务繁忙，请稍后重试")
+        }
+        sqlx::Error::Database(db) => match db.kind() {
+            ErrorKind::UniqueViolation => (ErrorCode::Conflict, "数据已存在，请勿重复提交"),
+            ErrorKind::ForeignKeyViolation => {
+                let parent_referenced = db
+                    .try_downcast_ref::<MySqlDatabaseError>()
+                    .is_some_and(|e| MYSQL_ROW_IS_REFERENCED.contains(&e.number()));
+                if parent_referenced {
+                    (ErrorCode::BadRequest, "数据仍被引用，不能删除或修改")
+                } else {
+                    // 插入 / 更新时引用的父行不存在，例如给已删除的帖子评论
+                    (ErrorCode::NotFound, "关联的数据不存在")
+                }
+            }
+            ErrorKind::NotNullViolation | ErrorKind::CheckViolation => {
+                (ErrorCode::BadRequest, "数据不满足约束")
+            }
+            _ => (ErrorCode::Database, "数据库错误"),
+        },
+        _ => (ErrorCode::Database, "数据库错误"),
+    }
+}
+
+/// 实现 IntoResponse：统一输出 { code, message, data: null, error_id } 信封，并记录日志
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
-            AppError::DatabaseError(e) => {
-                // tracing::error!("Database error: {:?}", e); // 确保你有 tracing 依赖，如果没有先注释掉
-                (StatusCode::INTERNAL_SERVER_ERROR, "数据库错误".to_string())
+        let code = self.code();
+        let envelope = match &self {
+            AppError::DatabaseError(e) => ErrorEnvelope::new(code, classify_db_error(e).1),
+            AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Conflict(msg)
+            | AppError::TooManyRequests(msg)
+            | AppError::InternalError(msg)
+            | AppError::MalformedBody(msg) => ErrorEnvelope::new(code, msg.clone()),
+
+            AppError::ValidationFailed(details) => {
+                // message 取第一个字段的错误，只看 message 的客户端也能提示
+                let message = details
+                    .first()
+                    .map(|d| d.message.clone())
+                    .unwrap_or_else(|| "参数校验失败".to_string());
+                ErrorEnvelope::new(code, message).with_details(details.clone())
             }
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AppError::ProtobufError(e) => ErrorEnvelope::new(code, format!("Protobuf 解析错误: {}", e)),
+        };
+        envelope.log(&self);
 
-            // ✨ 【改动3】对应的 HTTP 403 状态码
-            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+        let mut response = envelope.into_json_response();
+        if code == ErrorCode::ServiceUnavailable {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(POOL_RETRY_AFTER_SECS));
+        }
+        response
+    }
+}
 
-            AppError::InternalError(msg) => {
-                // tracing::error!("Internal error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, msg)
-            }
-            AppError::ProtobufError(e) => {
-                // tracing::error!("Protobuf error: {:?}", e);
-                (StatusCode::BAD_REQUEST, format!("Protobuf 解析错误: {}", e))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::error::DatabaseError;
+    use std::error::Error as StdError;
+
+    /// 只有 kind 的数据库错误，模拟驱动返回的约束冲突
+    #[derive(Debug)]
+    struct FakeDbError(ErrorKind);
+
+    impl fmt::Display for FakeDbError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    impl StdError for FakeDbError {}
+
+    impl DatabaseError for FakeDbError {
+        fn message(&self) -> &str {
+            "constraint violated"
+        }
+        fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+            self
+        }
+        fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+            self
+        }
+        fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+            self
+        }
+        fn kind(&self) -> ErrorKind {
+            match self.0 {
+                ErrorKind::UniqueViolation => ErrorKind::UniqueViolation,
+                ErrorKind::ForeignKeyViolation => ErrorKind::ForeignKeyViolation,
+                _ => ErrorKind::Other,
             }
-        };
+        }
+    }
+
+    fn db_error(kind: ErrorKind) -> AppError {
+        AppError::DatabaseError(sqlx::Error::Database(Box::new(FakeDbError(kind))))
+    }
+
+    /// 取出信封，去掉每次随机生成的 error_id
+    async fn body_json(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let error_id = json.as_object_mut().unwrap().remove("error_id").unwrap();
+        assert_eq!(error_id.as_str().unwrap().len(), 32);
+        json
+    }
+
+    #[tokio::test]
+    async fn test_errors_render_as_envelope() {
+        let response = AppError::Conflict("学号已存在".into()).into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            body_json(response).await,
+            serde_json::json!({ "code": 40901, "message": "学号已存在", "data": null })
+        );
 
-        // 返回简单的文本响应
-        (status, message).into_response()
+        let response = AppError::invalid_field("day_of_week", "星期几必须在 1-7 之间").into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_json(response).await,
+            serde_json::json!({
+                "code": 40002,
+                "message": "星期几必须在 1-7 之间",
+                "data": null,
+                "details": [{ "field": "day_of_week", "message": "星期几必须在 1-7 之间" }],
+            })
+        );
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_database_errors_are_classified() {
+        assert_eq!(db_error(ErrorKind::UniqueViolation).code(), ErrorCode::Conflict);
+        assert_eq!(db_error(ErrorKind::ForeignKeyViolation).code(), ErrorCode::NotFound);
+        assert_eq!(db_error(ErrorKind::Other).code(), ErrorCode::Database);
+        assert_eq!(AppError::DatabaseError(sqlx::Error::RowNotFound).code(), ErrorCode::NotFound);
+
+        // 具体的数据库错误不返回给客户端
+        let response = db_error(ErrorKind::Other).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body_json(response).await["message"], "数据库错误");
+
+        let response = AppError::DatabaseError(sqlx::Error::PoolTimedOut).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "3");
+        assert_eq!(body_json(response).await["code"], 50301);
+    }
+}
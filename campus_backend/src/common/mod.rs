@@ -1,5 +1,13 @@
 pub mod db;
+pub mod clock;
 pub mod error;
 pub mod state;
 pub mod auth;
-pub mod dev_tools;
\ No newline at end of file
+pub mod revocation;
+pub mod dev_tools;
+pub mod negotiate;
+pub mod proto;
+pub mod request_id;
+pub mod response;
+pub mod secrets;
+pub mod telemetry;
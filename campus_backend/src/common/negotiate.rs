@@ -0,0 +1,287 @@
+// src/common/negotiate.rs
+//
+// protobuf / JSON 内容协商：同一个 prost 消息既可以编码成 protobuf，也可以编码成 proto3 标准 JSON
+// （字段名 camelCase、int64 为字符串，由 pbjson 生成的 serde 实现负责）。
+// - 请求体：按 Content-Type，application/json（或 +json）解析 JSON，其余按 protobuf 解析
+// - 响应：按 Accept，JSON 的权重高于 protobuf 时返回 JSON，否则返回 protobuf（兼容现有客户端）
+// - 错误：negotiate_errors 中间件按 Accept 输出统一信封（JSON）或 common.ErrorResponse（protobuf）
+
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Request},
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prost::Message;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::common::error::AppError;
+use crate::common::proto::{self, ErrorResponse};
+use crate::common::response;
+
+pub const PROTOBUF: &str = "application/x-protobuf";
+pub const JSON: &str = "application/json";
+
+/// 消息编码格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Protobuf,
+    Json,
+}
+
+impl Format {
+    /// 按 Accept 选择响应格式；没有 Accept、`*/*` 或都不认识时默认 protobuf
+    pub fn from_accept(headers: &HeaderMap) -> Self {
+        let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+            return Self::Protobuf;
+        };
+
+        // (q, 是否具体类型, 格式)：权重相同时具体类型优先于通配符，再相同时取先出现的
+        let mut best: Option<(f32, bool, Self)> = None;
+        for range in accept.split(',') {
+            let mut params = range.split(';');
+            let media = params.next().unwrap_or("").trim().to_ascii_lowercase();
+            let q = params
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if q <= 0.0 {
+                continue;
+            }
+
+            let (specific, format) = match media.as_str() {
+                JSON => (true, Self::Json),
+                PROTOBUF | "application/protobuf" | "application/x-protobuffer" => (true, Self::Protobuf),
+                "*/*" | "application/*" => (false, Self::Protobuf),
+                _ => continue,
+            };
+            if best.is_none_or(|(bq, bs, _)| q > bq || (q == bq && specific && !bs)) {
+                best = Some((q, specific, format));
+            }
+        }
+
+        best.map(|(_, _, format)| format).unwrap_or(Self::Protobuf)
+    }
+
+    /// 按 Content-Type 判断请求体格式；没有 Content-Type 时按 protobuf 解析
+    pub fn from_content_type(headers: &HeaderMap) -> Self {
+        let media = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if media == JSON || media.ends_with("+json") {
+            Self::Json
+        } else {
+            Self::Protobuf
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Protobuf => PROTOBUF,
+            Self::Json => JSON,
+        }
+    }
+
+    pub fn encode<T: Message + Serialize>(self, msg: &T) -> Vec<u8> {
+        match self {
+            Self::Protobuf => msg.encode_to_vec(),
+            // pbjson 生成的实现只输出字符串、数字和嵌套对象，不会失败
+            Self::Json => serde_json::to_vec(msg).expect("protobuf message serializes to JSON"),
+        }
+    }
+
+    pub fn decode<T: Message + Default + DeserializeOwned>(self, body: &Bytes) -> Result<T, AppError> {
+        match self {
+            Self::Protobuf => Ok(T::decode(body.clone())?),
+            Self::Json => serde_json::from_slice(body)
+                .map_err(|e| AppError::MalformedBody(format!("JSON 解析错误: {}", e))),
+        }
+    }
+
+    /// 按当前格式输出消息
+    pub fn respond<T: Message + Serialize>(self, status: StatusCode, msg: &T) -> Response {
+        (
+            status,
+            [(header::CONTENT_TYPE, self.content_type())],
+            self.encode(msg),
+        )
+            .into_response()
+    }
+}
+
+/// 从 Accept 得到响应格式的提取器
+#[derive(Debug, Clone, Copy)]
+pub struct AcceptFormat(pub Format);
+
+impl AcceptFormat {
+    /// 以 200 返回消息
+    pub fn reply<T: Message + Serialize>(self, msg: &T) -> Response {
+        self.0.respond(StatusCode::OK, msg)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AcceptFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(Format::from_accept(&parts.headers)))
+    }
+}
+
+/// 按 Content-Type 解析 protobuf 或 JSON 请求体的提取器
+#[derive(Debug)]
+pub struct ProtoOrJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for ProtoOrJson<T>
+where
+    S: Send + Sync,
+    T: Message + Default + DeserializeOwned,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let format = Format::from_content_type(req.headers());
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| AppError::BadRequest(format!("读取请求体失败: {}", e)))?;
+        Ok(Self(format.decode(&body)?))
+    }
+}
+
+/// 把错误响应（AppError 信封、提取器拒绝等）改写成协商格式：JSON 时为统一信封，protobuf 时为 ErrorResponse
+pub async fn negotiate_errors(req: Request, next: Next) -> Response {
+    let format = Format::from_accept(req.headers());
+    let response = next.run(req).await;
+
+    let (parts, envelope) = match response::take_error_envelope(response).await {
+        Ok(taken) => taken,
+        Err(response) => return response,
+    };
+
+    let mut negotiated = match format {
+        Format::Json => envelope.into_json_response(),
+        Format::Protobuf => format.respond(
+            envelope.code.status(),
+            &ErrorResponse {
+                code: envelope.code.as_i32(),
+                message: envelope.message,
+                details: envelope
+                    .details
+                    .into_iter()
+                    .map(|d| proto::FieldError { field: d.field, message: d.message })
+                    .collect(),
+                error_id: envelope.error_id,
+            },
+        ),
+    };
+    response::restore_parts(&mut negotiated, &parts);
+    negotiated.headers_mut().insert(header::VARY, HeaderValue::from_static("accept"));
+    negotiated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_from_accept() {
+        assert_eq!(Format::from_accept(&HeaderMap::new()), Format::Protobuf);
+        assert_eq!(Format::from_accept(&accept("*/*")), Format::Protobuf);
+        assert_eq!(Format::from_accept(&accept("application/json")), Format::Json);
+        assert_eq!(Format::from_accept(&accept("application/json, */*;q=0.8")), Format::Json);
+        assert_eq!(Format::from_accept(&accept("*/*, application/json")), Format::Json);
+        assert_eq!(
+            Format::from_accept(&accept("application/json;q=0.5, application/x-protobuf")),
+            Format::Protobuf
+        );
+        assert_eq!(Format::from_accept(&accept("application/json;q=0, */*")), Format::Protobuf);
+        assert_eq!(Format::from_accept(&accept("text/html")), Format::Protobuf);
+    }
+
+    #[test]
+    fn test_encode_decode_both_formats() {
+        let msg = ErrorResponse {
+            code: 40002,
+            message: "星期几必须在 1-7 之间".into(),
+            details: vec![proto::FieldError { field: "day_of_week".into(), message: "星期几必须在 1-7 之间".into() }],
+            error_id: "0f8a".into(),
+        };
+
+        let json = Format::Json.encode(&msg);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({
+                "code": 40002,
+                "message": "星期几必须在 1-7 之间",
+                "details": [{ "field": "day_of_week", "message": "星期几必须在 1-7 之间" }],
+                "errorId": "0f8a",
+            })
+        );
+        for format in [Format::Json, Format::Protobuf] {
+            let bytes = Bytes::from(format.encode(&msg));
+            assert_eq!(format.decode::<ErrorResponse>(&bytes).unwrap(), msg);
+        }
+
+        // 两种格式解析失败都是 40003
+        let json_err = Format::Json.decode::<ErrorResponse>(&Bytes::from_static(b"{")).unwrap_err();
+        let proto_err = Format::Protobuf.decode::<ErrorResponse>(&Bytes::from_static(b"\xff")).unwrap_err();
+        assert_eq!(json_err.code(), response::ErrorCode::MalformedBody);
+        assert_eq!(proto_err.code(), response::ErrorCode::MalformedBody);
+    }
+
+    #[tokio::test]
+    async fn test_errors_follow_accept() {
+        let app = Router::new()
+            .route("/", get(|| async { AppError::NotFound("学期不存在".into()) }))
+            .route("/plain", get(|| async { (StatusCode::UNSUPPORTED_MEDIA_TYPE, "expected protobuf") }))
+            .layer(middleware::from_fn(negotiate_errors));
+
+        let request = Request::builder()
+            .uri("/")
+            .header(header::ACCEPT, JSON)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], JSON);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!((json["code"].as_i64(), json["message"].as_str()), (Some(40401), Some("学期不存在")));
+
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROTOBUF);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error = ErrorResponse::decode(body).unwrap();
+        assert_eq!((error.code, error.message.as_str()), (40401, "学期不存在"));
+        assert_eq!(error.error_id.len(), 32);
+
+        // 非 AppError 的纯文本错误同样改写
+        let request = Request::builder().uri("/plain").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(ErrorResponse::decode(body).unwrap().code, 41501);
+    }
+}
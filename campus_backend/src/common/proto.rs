@@ -0,0 +1,6 @@
+// src/common/proto.rs
+//
+// proto/common.proto 生成的各模块共用消息（ErrorResponse 等）
+
+include!(concat!(env!("OUT_DIR"), "/common.rs"));
+include!(concat!(env!("OUT_DIR"), "/common.serde.rs"));
@@ -0,0 +1,111 @@
+// src/common/request_id.rs
+//
+// 请求 id：客户端（或网关）带了合法的 X-Request-Id 就沿用，否则生成一个。
+// - 写回请求头，访问日志的 span 从请求头读取
+// - 在处理请求的任务里可以通过 current() 取到，AppError 的 error_id 即为请求 id
+// - 响应头里原样返回，客户端反馈问题时带上即可查到整条请求的日志
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 沿用客户端请求 id 时的最大长度
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// 当前请求的 id（不在请求处理过程中时为 None）
+pub fn current() -> Option<String> {
+    CURRENT.try_with(|id| id.clone()).ok()
+}
+
+/// 只接受可见 ASCII 字符，避免把任意内容写进日志
+fn accept_client_id(value: &HeaderValue) -> Option<String> {
+    let id = value.to_str().ok()?.trim();
+    let valid = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| id.to_string())
+}
+
+/// 分配或沿用请求 id 的中间件
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(accept_client_id)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+
+    // 上面已经过滤成可见 ASCII，一定是合法的头部值
+    let value = HeaderValue::from_str(&id).expect("request id is a valid header value");
+    req.headers_mut().insert(REQUEST_ID_HEADER, value.clone());
+
+    let mut response = CURRENT.scope(id, next.run(req)).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::error::AppError;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(|| async { current().unwrap_or_default() }))
+            .route("/error", get(|| async { AppError::NotFound("学期不存在".into()) }))
+            .layer(middleware::from_fn(request_id))
+    }
+
+    async fn body_string(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_propagates_or_assigns_id() {
+        let request = Request::builder()
+            .uri("/")
+            .header(&REQUEST_ID_HEADER, "gw-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[&REQUEST_ID_HEADER], "gw-123");
+        assert_eq!(body_string(response).await, "gw-123");
+
+        // 不合法的 id 被替换
+        let request = Request::builder()
+            .uri("/")
+            .header(&REQUEST_ID_HEADER, "a b")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        let assigned = response.headers()[&REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        assert_eq!(assigned.len(), 32);
+        assert_eq!(body_string(response).await, assigned);
+
+        assert_eq!(current(), None);
+    }
+
+    #[tokio::test]
+    async fn test_error_id_is_request_id() {
+        let request = Request::builder()
+            .uri("/error")
+            .header(&REQUEST_ID_HEADER, "req-42")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(json["error_id"], "req-42");
+    }
+}
@@ -0,0 +1,339 @@
+// src/common/response.rs
+//
+// 统一响应信封与错误码表。
+// - 所有模块的 JSON 响应都是 { code, message, data }，成功时 code = 0
+// - 失败时 code 取自 ErrorCode（五位数，前三位与 HTTP 状态码一致），参数校验失败另带 details；
+//   error_id 即请求 id（响应头 X-Request-Id），与服务端日志中的 request_id 对应，便于按用户反馈查日志
+// - protobuf 接口的消息自带 code / message，错误时返回 common.ErrorResponse，码值相同
+
+use axum::{
+    body::Body,
+    http::{header, response::Parts, StatusCode},
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::common::request_id;
+
+/// 成功响应的 code
+pub const OK_CODE: i32 = 0;
+
+/// 改写错误响应时最多读取的响应体长度
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// 错误码表
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// 请求参数错误
+    BadRequest = 40001,
+    /// 参数校验失败（details 里带具体字段）
+    ValidationFailed = 40002,
+    /// 请求体无法解析（JSON / protobuf）
+    MalformedBody = 40003,
+    /// 未登录或 Token 无效
+    Unauthorized = 40101,
+    /// 权限不足
+    Forbidden = 40301,
+    /// 资源不存在
+    NotFound = 40401,
+    /// 请求方法不支持
+    MethodNotAllowed = 40501,
+    /// 与现有数据冲突（重复、时间冲突、状态不允许等）
+    Conflict = 40901,
+    /// 请求体过大
+    PayloadTooLarge = 41301,
+    /// 不支持的 Content-Type
+    UnsupportedMediaType = 41501,
+    /// 请求过于频繁
+    TooManyRequests = 42901,
+    /// 服务器内部错误
+    Internal = 50001,
+    /// 数据库错误
+    Database = 50002,
+    /// 服务暂不可用
+    ServiceUnavailable = 50301,
+}
+
+impl ErrorCode {
+    pub fn as_i32(self) -> i32 {
+        self as i32
+    }
+
+    pub fn status(self) -> StatusCode {
+        match self {
+            Self::BadRequest | Self::ValidationFailed | Self::MalformedBody => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            Self::Conflict => StatusCode::CONFLICT,
+            Self::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            Self::Internal | Self::Database => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// 非 AppError 产生的错误（提取器拒绝、未匹配路由等）按状态码归类
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Unauthorized,
+            StatusCode::FORBIDDEN => Self::Forbidden,
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::METHOD_NOT_ALLOWED => Self::MethodNotAllowed,
+            StatusCode::CONFLICT => Self::Conflict,
+            StatusCode::PAYLOAD_TOO_LARGE => Self::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => Self::UnsupportedMediaType,
+            // axum 的 Json 提取器：422 为字段类型不匹配，400 为语法错误
+            StatusCode::UNPROCESSABLE_ENTITY => Self::ValidationFailed,
+            StatusCode::TOO_MANY_REQUESTS => Self::TooManyRequests,
+            StatusCode::SERVICE_UNAVAILABLE => Self::ServiceUnavailable,
+            s if s.is_server_error() => Self::Internal,
+            _ => Self::BadRequest,
+        }
+    }
+}
+
+/// 参数校验失败时的单个字段错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// 统一的 API 响应信封
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    pub code: i32,
+    pub message: String,
+    pub data: Option<T>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_id: Option<String>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn ok(data: T) -> Self {
+        Self {
+            code: OK_CODE,
+            message: "ok".to_string(),
+            data: Some(data),
+            details: Vec::new(),
+            error_id: None,
+        }
+    }
+}
+
+impl ApiResponse<()> {
+    /// 没有业务数据的成功响应（data 为 null）
+    pub fn empty() -> Self {
+        Self {
+            code: OK_CODE,
+            message: "ok".to_string(),
+            data: None,
+            details: Vec::new(),
+            error_id: None,
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+/// 错误响应的内容。AppError 转成响应时放进 extensions，协商层据此改写成 protobuf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorEnvelope {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Vec<FieldError>,
+    /// 关联日志用的错误 id：请求处理中为请求 id，否则随机生成
+    pub error_id: String,
+}
+
+impl ErrorEnvelope {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: Vec::new(),
+            error_id: request_id::current().unwrap_or_else(|| Uuid::new_v4().simple().to_string()),
+        }
+    }
+
+    pub fn with_details(mut self, details: Vec<FieldError>) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// 记录错误日志：5xx 为 error，4xx 为 warn；cause 是不返回给客户端的内部原因
+    pub fn log(&self, cause: &dyn std::fmt::Display) {
+        if self.code.status().is_server_error() {
+            tracing::error!(error_id = %self.error_id, code = self.code.as_i32(), "{}", cause);
+        } else {
+            tracing::warn!(error_id = %self.error_id, code = self.code.as_i32(), "{}", cause);
+        }
+    }
+
+    /// 以 JSON 信封输出，状态码由错误码决定
+    pub fn into_json_response(self) -> Response {
+        let body = ApiResponse::<()> {
+            code: self.code.as_i32(),
+            message: self.message.clone(),
+            data: None,
+            details: self.details.clone(),
+            error_id: Some(self.error_id.clone()),
+        };
+        let mut response = (self.code.status(), Json(body)).into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+/// 取出错误响应的信封：AppError 生成的直接取 extensions，
+/// 其他纯文本错误（提取器拒绝、未匹配路由等）读出正文包装成信封。
+/// 不是错误响应、或已经是 JSON / protobuf 编码的非 AppError 响应原样退回。
+pub async fn take_error_envelope(response: Response) -> Result<(Parts, ErrorEnvelope), Response> {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Err(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    if let Some(envelope) = parts.extensions.remove::<ErrorEnvelope>() {
+        return Ok((parts, envelope));
+    }
+
+    let plain = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.starts_with("text/plain"));
+    if !plain {
+        return Err(Response::from_parts(parts, body));
+    }
+
+    let message = axum::body::to_bytes(body, MAX_ERROR_BODY)
+        .await
+        .ok()
+        .map(|b| String::from_utf8_lossy(&b).trim().to_string())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or_default().to_string());
+
+    let envelope = ErrorEnvelope::new(ErrorCode::from_status(status), message);
+    envelope.log(&envelope.message);
+    Ok((parts, envelope))
+}
+
+/// 改写后的响应沿用原状态码（如 422、413），并复制正文以外的头（如 WWW-Authenticate、Allow）
+pub fn restore_parts(response: &mut Response, parts: &Parts) {
+    *response.status_mut() = parts.status;
+    for (name, value) in parts.headers.iter() {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            response.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+}
+
+/// 全局中间件：非 AppError 产生的纯文本错误也输出 JSON 信封
+pub async fn envelope_errors(req: Request<Body>, next: Next) -> Response {
+    let response = next.run(req).await;
+    if response.extensions().get::<ErrorEnvelope>().is_some() {
+        return response;
+    }
+
+    match take_error_envelope(response).await {
+        Ok((parts, envelope)) => {
+            let mut wrapped = envelope.into_json_response();
+            restore_parts(&mut wrapped, &parts);
+            wrapped
+        }
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    /// 取出错误信封，去掉每次随机生成的 error_id
+    async fn json_body(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), MAX_ERROR_BODY).await.unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let error_id = json.as_object_mut().unwrap().remove("error_id").unwrap();
+        assert_eq!(error_id.as_str().unwrap().len(), 32);
+        json
+    }
+
+    #[test]
+    fn test_codes_match_status() {
+        for code in [
+            ErrorCode::BadRequest,
+            ErrorCode::ValidationFailed,
+            ErrorCode::Unauthorized,
+            ErrorCode::NotFound,
+            ErrorCode::Conflict,
+            ErrorCode::TooManyRequests,
+            ErrorCode::Database,
+            ErrorCode::ServiceUnavailable,
+        ] {
+            assert_eq!(code.as_i32() / 100, code.status().as_u16() as i32, "{:?}", code);
+        }
+        assert_eq!(ErrorCode::from_status(StatusCode::CONFLICT), ErrorCode::Conflict);
+        assert_eq!(ErrorCode::from_status(StatusCode::BAD_GATEWAY), ErrorCode::Internal);
+    }
+
+    #[test]
+    fn test_envelope_shape() {
+        let ok = serde_json::to_value(ApiResponse::ok(vec![1, 2])).unwrap();
+        assert_eq!(ok, serde_json::json!({ "code": 0, "message": "ok", "data": [1, 2] }));
+
+        let empty = serde_json::to_value(ApiResponse::empty()).unwrap();
+        assert_eq!(empty, serde_json::json!({ "code": 0, "message": "ok", "data": null }));
+    }
+
+    #[tokio::test]
+    async fn test_plain_errors_are_wrapped() {
+        let app = Router::new()
+            .route("/", get(|| async { (StatusCode::UNPROCESSABLE_ENTITY, "missing field `name`") }))
+            .layer(middleware::from_fn(envelope_errors));
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            json_body(response).await,
+            serde_json::json!({ "code": 40002, "message": "missing field `name`", "data": null })
+        );
+
+        // 未匹配的路由
+        let response = app
+            .oneshot(Request::builder().uri("/nope").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(json_body(response).await["code"], 40401);
+    }
+}
@@ -0,0 +1,373 @@
+// src/common/revocation.rs
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use redis::{aio::ConnectionManager, AsyncCommands};
+
+use crate::common::error::AppError;
+
+/// Token 吊销列表（按 jti 记录），用于退出登录后让 JWT 立即失效
+/// 同时记录每个 refresh token 家族当前有效的 jti，用于轮换和重放检测
+#[async_trait]
+pub trait RevocationStore: Send + Sync {
+    /// 吊销 jti，记录保留到 `expires_at`（Unix 秒，即 Token 的 exp）为止
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError>;
+
+    /// jti 是否已被吊销
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError>;
+
+    /// 新家族：记录当前有效的 refresh token jti
+    async fn set_refresh_head(&self, family: &str, jti: &str, expires_at: i64) -> Result<(), AppError>;
+
+    /// 原子地把家族当前 jti 从 `current` 换成 `next`
+    /// 返回 false 表示 `current` 已不是最新的（重放）或家族不存在
+    async fn rotate_refresh_head(
+        &self,
+        family: &str,
+        current: &str,
+        next: &str,
+        expires_at: i64,
+    ) -> Result<bool, AppError>;
+
+    /// 吊销整个家族（该家族签发的 access / refresh token 全部失效）
+    async fn revoke_family(&self, family: &str, expires_at: i64) -> Result<(), AppError>;
+
+    /// 家族是否已被吊销
+    async fn is_family_revoked(&self, family: &str) -> Result<bool, AppError>;
+
+    /// 记录用户名下的家族（签发 / 轮换时调用，`expires_at` 为家族最新的过期时间）
+    async fn track_user_family(&self, user_id: &str, family: &str, expires_at: i64) -> Result<(), AppError>;
+
+    /// 吊销用户名下所有仍有效的家族（修改密码等场景，让所有设备重新登录）
+    async fn revoke_user_families(&self, user_id: &str) -> Result<(), AppError>;
+}
+
+/// 距离 `expires_at` 还剩多少秒
+fn ttl_secs(expires_at: i64) -> i64 {
+    expires_at - chrono::Utc::now().timestamp()
+}
+
+// ==========================================
+// Redis 实现（生产环境，多实例共享）
+// ==========================================
+
+const REDIS_KEY_PREFIX: &str = "auth:revoked:";
+const REDIS_FAMILY_HEAD_PREFIX: &str = "auth:refresh-head:";
+const REDIS_FAMILY_REVOKED_PREFIX: &str = "auth:revoked-family:";
+/// 有序集合：member 为家族 ID，score 为家族过期时间
+const REDIS_USER_FAMILIES_PREFIX: &str = "auth:user-families:";
+
+/// 比较并交换：只有当前值等于 ARGV[1] 时才写入 ARGV[2]
+const ROTATE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+    return 1
+end
+return 0
+"#;
+
+fn redis_err(e: redis::RedisError) -> AppError {
+    AppError::InternalError(format!("Redis error: {}", e))
+}
+
+pub struct RedisRevocationStore {
+    conn: ConnectionManager,
+}
+
+impl RedisRevocationStore {
+    pub async fn connect(redis_url: &str) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(redis_url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self { conn })
+    }
+}
+
+#[async_trait]
+impl RevocationStore for RedisRevocationStore {
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        let ttl = ttl_secs(expires_at);
+        if ttl <= 0 {
+            // 已经过期的 Token 不需要再记录
+            return Ok(());
+        }
+
+        let mut conn = self.conn.clone();
+        conn.set_ex::<_, _, ()>(format!("{}{}", REDIS_KEY_PREFIX, jti), 1, ttl as u64)
+            .await
+            .map_err(redis_err)
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError> {
+        let mut conn = self.conn.clone();
+        conn.exists(format!("{}{}", REDIS_KEY_PREFIX, jti))
+            .await
+            .map_err(redis_err)
+    }
+
+    async fn set_refresh_head(&self, family: &str, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        let ttl = ttl_secs(expires_at);
+        if ttl <= 0 {
+            return Ok(());
+        }
+
+        let mut conn = self.conn.clone();
+        conn.set_ex::<_, _, ()>(format!("{}{}", REDIS_FAMILY_HEAD_PREFIX, family), jti, ttl as u64)
+            .await
+            .map_err(redis_err)
+    }
+
+    async fn rotate_refresh_head(
+        &self,
+        family: &str,
+        current: &str,
+        next: &str,
+        expires_at: i64,
+    ) -> Result<bool, AppError> {
+        let ttl = ttl_secs(expires_at).max(1);
+
+        let mut conn = self.conn.clone();
+        let swapped: i32 = redis::Script::new(ROTATE_SCRIPT)
+            .key(format!("{}{}", REDIS_FAMILY_HEAD_PREFIX, family))
+            .arg(current)
+            .arg(next)
+            .arg(ttl)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        Ok(swapped == 1)
+    }
+
+    async fn revoke_family(&self, family: &str, expires_at: i64) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        conn.del::<_, ()>(format!("{}{}", REDIS_FAMILY_HEAD_PREFIX, family))
+            .await
+            .map_err(redis_err)?;
+
+        let ttl = ttl_secs(expires_at);
+        if ttl <= 0 {
+            return Ok(());
+        }
+        conn.set_ex::<_, _, ()>(format!("{}{}", REDIS_FAMILY_REVOKED_PREFIX, family), 1, ttl as u64)
+            .await
+            .map_err(redis_err)
+    }
+
+    async fn is_family_revoked(&self, family: &str) -> Result<bool, AppError> {
+        let mut conn = self.conn.clone();
+        conn.exists(format!("{}{}", REDIS_FAMILY_REVOKED_PREFIX, family))
+            .await
+            .map_err(redis_err)
+    }
+
+    async fn track_user_family(&self, user_id: &str, family: &str, expires_at: i64) -> Result<(), AppError> {
+        let ttl = ttl_secs(expires_at);
+        if ttl <= 0 {
+            return Ok(());
+        }
+
+        let key = format!("{}{}", REDIS_USER_FAMILIES_PREFIX, user_id);
+        let now = chrono::Utc::now().timestamp();
+        let mut conn = self.conn.clone();
+        conn.zrembyscore::<_, _, _, ()>(&key, "-inf", now)
+            .await
+            .map_err(redis_err)?;
+        conn.zadd::<_, _, _, ()>(&key, family, expires_at)
+            .await
+            .map_err(redis_err)?;
+        // 每次写入的都是最新签发的家族，过期时间不早于集合里的其他家族
+        conn.expire::<_, ()>(&key, ttl)
+            .await
+            .map_err(redis_err)
+    }
+
+    async fn revoke_user_families(&self, user_id: &str) -> Result<(), AppError> {
+        let key = format!("{}{}", REDIS_USER_FAMILIES_PREFIX, user_id);
+        let now = chrono::Utc::now().timestamp();
+        let mut conn = self.conn.clone();
+        let families: Vec<(String, i64)> = conn
+            .zrangebyscore_withscores(&key, now, "+inf")
+            .await
+            .map_err(redis_err)?;
+
+        for (family, expires_at) in families {
+            self.revoke_family(&family, expires_at).await?;
+        }
+
+        conn.del::<_, ()>(&key).await.map_err(redis_err)
+    }
+}
+
+// ==========================================
+// 内存实现（单机开发 / 测试）
+// ==========================================
+
+#[derive(Default)]
+pub struct InMemoryRevocationStore {
+    // jti -> exp
+    entries: Mutex<HashMap<String, i64>>,
+    // family -> (当前 refresh jti, exp)
+    heads: Mutex<HashMap<String, (String, i64)>>,
+    // family -> exp
+    revoked_families: Mutex<HashMap<String, i64>>,
+    // user_id -> (family -> exp)
+    user_families: Mutex<HashMap<String, HashMap<String, i64>>>,
+}
+
+impl InMemoryRevocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RevocationStore for InMemoryRevocationStore {
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().timestamp();
+        let mut entries = self.entries.lock().unwrap();
+
+        // 顺手清理已经自然过期的记录，避免无限增长
+        entries.retain(|_, exp| *exp > now);
+
+        if expires_at > now {
+            entries.insert(jti.to_owned(), expires_at);
+        }
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError> {
+        let now = chrono::Utc::now().timestamp();
+        let entries = self.entries.lock().unwrap();
+        Ok(entries.get(jti).is_some_and(|exp| *exp > now))
+    }
+
+    async fn set_refresh_head(&self, family: &str, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().timestamp();
+        let mut heads = self.heads.lock().unwrap();
+        heads.retain(|_, (_, exp)| *exp > now);
+        heads.insert(family.to_owned(), (jti.to_owned(), expires_at));
+        Ok(())
+    }
+
+    async fn rotate_refresh_head(
+        &self,
+        family: &str,
+        current: &str,
+        next: &str,
+        expires_at: i64,
+    ) -> Result<bool, AppError> {
+        let now = chrono::Utc::now().timestamp();
+        let mut heads = self.heads.lock().unwrap();
+
+        match heads.get_mut(family) {
+            Some((jti, exp)) if *exp > now && jti == current => {
+                *jti = next.to_owned();
+                *exp = expires_at;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn revoke_family(&self, family: &str, expires_at: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().timestamp();
+        self.heads.lock().unwrap().remove(family);
+
+        let mut revoked = self.revoked_families.lock().unwrap();
+        revoked.retain(|_, exp| *exp > now);
+        if expires_at > now {
+            revoked.insert(family.to_owned(), expires_at);
+        }
+        Ok(())
+    }
+
+    async fn is_family_revoked(&self, family: &str) -> Result<bool, AppError> {
+        let now = chrono::Utc::now().timestamp();
+        let revoked = self.revoked_families.lock().unwrap();
+        Ok(revoked.get(family).is_some_and(|exp| *exp > now))
+    }
+
+    async fn track_user_family(&self, user_id: &str, family: &str, expires_at: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().timestamp();
+        let mut users = self.user_families.lock().unwrap();
+        let families = users.entry(user_id.to_owned()).or_default();
+        families.retain(|_, exp| *exp > now);
+        families.insert(family.to_owned(), expires_at);
+        Ok(())
+    }
+
+    async fn revoke_user_families(&self, user_id: &str) -> Result<(), AppError> {
+        let families = self.user_families.lock().unwrap().remove(user_id).unwrap_or_default();
+        for (family, expires_at) in families {
+            self.revoke_family(&family, expires_at).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_memory_revoke() {
+        let store = InMemoryRevocationStore::new();
+        let exp = chrono::Utc::now().timestamp() + 60;
+
+        assert!(!store.is_revoked("jti-1").await.unwrap());
+
+        store.revoke("jti-1", exp).await.unwrap();
+        assert!(store.is_revoked("jti-1").await.unwrap());
+        assert!(!store.is_revoked("jti-2").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_rotate_refresh_head() {
+        let store = InMemoryRevocationStore::new();
+        let exp = chrono::Utc::now().timestamp() + 60;
+
+        store.set_refresh_head("fam", "r1", exp).await.unwrap();
+        assert!(store.rotate_refresh_head("fam", "r1", "r2", exp).await.unwrap());
+
+        // r1 已经被换掉，再次使用视为重放
+        assert!(!store.rotate_refresh_head("fam", "r1", "r3", exp).await.unwrap());
+        assert!(!store.rotate_refresh_head("unknown", "r1", "r2", exp).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_revoke_family() {
+        let store = InMemoryRevocationStore::new();
+        let exp = chrono::Utc::now().timestamp() + 60;
+
+        store.set_refresh_head("fam", "r1", exp).await.unwrap();
+        store.revoke_family("fam", exp).await.unwrap();
+
+        assert!(store.is_family_revoked("fam").await.unwrap());
+        assert!(!store.rotate_refresh_head("fam", "r1", "r2", exp).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_revoke_user_families() {
+        let store = InMemoryRevocationStore::new();
+        let exp = chrono::Utc::now().timestamp() + 60;
+
+        store.track_user_family("u1", "fam-1", exp).await.unwrap();
+        store.track_user_family("u1", "fam-2", exp).await.unwrap();
+        store.track_user_family("u2", "fam-3", exp).await.unwrap();
+        store.revoke_user_families("u1").await.unwrap();
+
+        assert!(store.is_family_revoked("fam-1").await.unwrap());
+        assert!(store.is_family_revoked("fam-2").await.unwrap());
+        assert!(!store.is_family_revoked("fam-3").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_ignores_expired_tokens() {
+        let store = InMemoryRevocationStore::new();
+        let exp = chrono::Utc::now().timestamp() - 1;
+
+        store.revoke("jti-1", exp).await.unwrap();
+        assert!(!store.is_revoked("jti-1").await.unwrap());
+    }
+}
@@ -0,0 +1,47 @@
+// src/common/secrets.rs
+//
+// 各类签名用的密钥（课表订阅链接、活动签到码等）。
+// 优先读取各自的环境变量；未配置时从 JWT_SECRET 按用途派生：
+//   key = HMAC-SHA256(JWT_SECRET, "campus-backend:{label}")
+// 派生出的密钥互不相同，泄露其中一个既推不出 JWT_SECRET，也伪造不了其他用途的签名。
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 从主密钥派生某个用途的密钥
+pub fn derive_key(master: &[u8], label: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(master).expect("HMAC accepts any key length");
+    mac.update(format!("campus-backend:{}", label).as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 读取签名密钥：环境变量 `var` 有值时直接使用，否则从 JWT_SECRET 派生
+pub fn signing_key(var: &str, label: &str) -> Vec<u8> {
+    match std::env::var(var) {
+        Ok(secret) if !secret.is_empty() => secret.into_bytes(),
+        _ => {
+            let master = std::env::var("JWT_SECRET")
+                .unwrap_or_else(|_| panic!("{} or JWT_SECRET must be set", var));
+            derive_key(master.as_bytes(), label)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_keys_are_separated() {
+        let feed = derive_key(b"jwt-secret", "schedule-feed");
+        let checkin = derive_key(b"jwt-secret", "activity-checkin");
+
+        assert_eq!(feed.len(), 32);
+        assert_eq!(feed, derive_key(b"jwt-secret", "schedule-feed"));
+        assert_ne!(feed, checkin);
+        assert_ne!(feed, b"jwt-secret".to_vec());
+        assert_ne!(feed, derive_key(b"other-secret", "schedule-feed"));
+    }
+}
@@ -2,10 +2,19 @@ use std::sync::Arc;
 use sqlx::MySqlPool; // ✨ 修正：改成 MySqlPool
 use axum::extract::FromRef;
 
+use crate::common::revocation::RevocationStore;
+use crate::modules::activity::events::ActivityEventBus;
+use crate::modules::activity::service::ActivityService;
+use crate::modules::course::calendar::SectionTimes;
+use crate::modules::course::feed::FeedSigner;
+
 #[derive(Clone, Debug)]
 pub struct JwtConfig {
     pub secret: String,
+    /// access token 有效期（秒）
     pub expiration: i64,
+    /// refresh token 有效期（秒）
+    pub refresh_expiration: i64,
 }
 
 impl JwtConfig {
@@ -13,9 +22,13 @@ impl JwtConfig {
         Self {
             secret: std::env::var("JWT_SECRET").expect("JWT_SECRET must be set"),
             expiration: std::env::var("JWT_EXPIRATION")
-                .unwrap_or_else(|_| "86400".to_string())
+                .unwrap_or_else(|_| "900".to_string())
                 .parse()
                 .expect("JWT_EXPIRATION must be a number"),
+            refresh_expiration: std::env::var("JWT_REFRESH_EXPIRATION")
+                .unwrap_or_else(|_| "2592000".to_string())
+                .parse()
+                .expect("JWT_REFRESH_EXPIRATION must be a number"),
         }
     }
 }
@@ -24,6 +37,47 @@ impl JwtConfig {
 pub struct AppState {
     pub jwt_config: Arc<JwtConfig>,
     pub pool: MySqlPool, // ✨ 修正：改成 MySqlPool
+    /// 已注销 Token 的吊销列表（Redis / 内存）
+    pub revocation: Arc<dyn RevocationStore>,
+    /// 活动模块业务实现
+    pub activity: Arc<dyn ActivityService>,
+    /// 活动状态流转事件，其他模块通过 subscribe() 订阅
+    pub activity_events: ActivityEventBus,
+    /// 默认节次作息表（课表导出 .ics 时换算上下课时间）
+    pub section_times: Arc<SectionTimes>,
+    /// 课表订阅链接签名
+    pub schedule_feed: FeedSigner,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// 测试用状态：JWT / 签名密钥均为 "test-secret"，吊销列表在内存中，
+    /// 数据库为惰性连接，测试过程中不会真正访问。新增字段时只需在这里补上
+    pub fn for_tests() -> Self {
+        use crate::common::revocation::InMemoryRevocationStore;
+        use crate::modules::activity::{checkin::CheckinSigner, service::ActivityServiceImpl};
+
+        let pool = MySqlPool::connect_lazy("mysql://localhost/test").unwrap();
+        // 与 main.rs 一致：活动服务发布事件和订阅方用的是同一条总线
+        let activity_events = ActivityEventBus::new();
+        Self {
+            jwt_config: Arc::new(JwtConfig {
+                secret: "test-secret".to_string(),
+                expiration: 900,
+                refresh_expiration: 3600,
+            }),
+            activity: ActivityServiceImpl::new(
+                pool.clone(),
+                CheckinSigner::new("test-secret", 30),
+                activity_events.clone(),
+            ),
+            activity_events,
+            section_times: Arc::new(SectionTimes::default()),
+            schedule_feed: FeedSigner::new("test-secret", None),
+            pool,
+            revocation: Arc::new(InMemoryRevocationStore::new()),
+        }
+    }
 }
 
 // ✨ 修正：为 MySqlPool 实现 FromRef
@@ -37,4 +91,21 @@ impl FromRef<AppState> for Arc<JwtConfig> {
     fn from_ref(state: &AppState) -> Self {
         state.jwt_config.clone()
     }
-}
\ No newline at end of file
+}
+
+impl FromRef<AppState> for Arc<dyn RevocationStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.revocation.clone()
+    }
+}
+impl FromRef<AppState> for Arc<dyn ActivityService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.activity.clone()
+    }
+}
+
+impl FromRef<AppState> for ActivityEventBus {
+    fn from_ref(state: &AppState) -> Self {
+        state.activity_events.clone()
+    }
+}
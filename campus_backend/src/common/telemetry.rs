@@ -0,0 +1,76 @@
+// src/common/telemetry.rs
+//
+// 日志初始化与访问日志。
+// - LOG_FORMAT=json 时输出一行一个 JSON（方便采集），否则为普通文本；级别仍由 RUST_LOG 控制
+// - 每个请求一个 span：method、路由模板、request_id、user_id（AuthUser 提取成功后填入）、
+//   status、latency_ms，请求内的其他日志都挂在这个 span 下
+
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::MatchedPath,
+    http::{Request, Response},
+};
+use tracing::{field::Empty, Span};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::common::request_id::REQUEST_ID_HEADER;
+
+/// 初始化全局日志
+pub fn init() {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| "campus_backend=debug,tower_http=debug".into());
+
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|v| v.eq_ignore_ascii_case("json"));
+    let registry = tracing_subscriber::registry().with(filter);
+    if json {
+        registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(false),
+            )
+            .init();
+    } else {
+        registry.with(tracing_subscriber::fmt::layer()).init();
+    }
+}
+
+/// 为请求创建 span；路由模板取自 MatchedPath（未匹配时用原始路径）
+pub fn make_span(req: &Request<Body>) -> Span {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %req.method(),
+        route = %route,
+        request_id = %request_id,
+        user_id = Empty,
+        status = Empty,
+        latency_ms = Empty,
+    )
+}
+
+/// 请求完成时记录状态码和耗时
+pub fn on_response<B>(res: &Response<B>, latency: Duration, span: &Span) {
+    span.record("status", res.status().as_u16());
+    span.record("latency_ms", latency.as_millis() as u64);
+    tracing::info!("request completed");
+}
+
+/// 已认证的用户 id 写进当前请求的 span
+pub fn record_user(user_id: &str) {
+    Span::current().record("user_id", user_id);
+}
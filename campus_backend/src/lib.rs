@@ -0,0 +1,3 @@
+// 服务端各模块的库入口：main.rs 与 FFI (cdylib) 共用
+pub mod common;
+pub mod modules;
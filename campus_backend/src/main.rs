@@ -1,28 +1,34 @@
-mod common;
-mod modules;
-
 use axum::Router;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use crate::common::state::{AppState, JwtConfig};
+use tower_http::{
+    cors::{Any, CorsLayer},
+    trace::TraceLayer,
+};
+use campus_backend::common::{
+    self,
+    clock::SystemClock,
+    revocation::{InMemoryRevocationStore, RedisRevocationStore, RevocationStore},
+    state::{AppState, JwtConfig},
+};
+use campus_backend::modules::{
+    self,
+    activity::{
+        checkin::CheckinSigner, events::ActivityEventBus, scheduler::LifecycleScheduler,
+        service::ActivityServiceImpl,
+    },
+    course::{calendar::SectionTimes, feed::FeedSigner},
+};
 
 #[tokio::main]
 async fn main() {
-    // 初始化日志
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "campus_backend=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // 使用 dotenvy 加载环境变量（先于日志初始化，.env 里的 RUST_LOG / LOG_FORMAT 才生效）
+    dotenvy::dotenv().ok();
 
-    tracing::info!("Campus Backend is starting...");
+    // 初始化日志（LOG_FORMAT=json 时输出 JSON）
+    common::telemetry::init();
 
-    // 使用 dotenvy 加载环境变量
-    dotenvy::dotenv().ok();
+    tracing::info!("Campus Backend is starting...");
 
     // 读取数据库地址
     let database_url = std::env::var("DATABASE_URL")
@@ -37,18 +43,51 @@ async fn main() {
 
     // 初始化 JWT 配置
     let jwt_config = JwtConfig::from_env();
+
+    // Token 吊销列表：配置了 REDIS_URL 就用 Redis（多实例共享），否则退回内存
+    let revocation: Arc<dyn RevocationStore> = match std::env::var("REDIS_URL") {
+        Ok(redis_url) => {
+            let store = RedisRevocationStore::connect(&redis_url)
+                .await
+                .expect("Failed to connect to Redis");
+            tracing::info!("Token revocation store: Redis");
+            Arc::new(store)
+        }
+        Err(_) => {
+            tracing::warn!("REDIS_URL not set, token revocation list is kept in memory");
+            Arc::new(InMemoryRevocationStore::new())
+        }
+    };
+
+    // 活动状态流转事件总线，通知等模块从 AppState 订阅
+    let activity_events = ActivityEventBus::new();
+
     let state = AppState {
+        activity: ActivityServiceImpl::new(
+            pool.clone(),
+            CheckinSigner::from_env(),
+            activity_events.clone(),
+        ),
+        activity_events: activity_events.clone(),
+        section_times: Arc::new(SectionTimes::from_env()),
+        schedule_feed: FeedSigner::from_env(),
         pool,
         jwt_config: Arc::new(jwt_config),
+        revocation,
     };
 
+    // 活动生命周期定时任务（自动结束活动、开关报名）
+    LifecycleScheduler::from_env(state.pool.clone(), Arc::new(SystemClock), activity_events).spawn();
+
     // 设置 CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        // 让浏览器端能读到请求 id，反馈问题时附上
+        .expose_headers([common::request_id::REQUEST_ID_HEADER]);
 
-    // 注册路由（course + user + forum）
+    // 注册路由（course + user + forum + activity）
     let app = Router::new()
         // 课程模块
         .merge(modules::course::router())
@@ -56,7 +95,21 @@ async fn main() {
         .merge(modules::user::router())
         // 论坛模块（原 bbs）
         .merge(modules::forum::router())
+        // 活动模块
+        .merge(modules::activity::router())
 
+        // 提取器拒绝、未匹配路由等纯文本错误也输出统一信封
+        .layer(axum::middleware::from_fn(common::response::envelope_errors))
+        // 访问日志：每个请求一个 span，记录路由、用户、状态码和耗时
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(common::telemetry::make_span)
+                .on_response(common::telemetry::on_response)
+                // 错误已由 AppError / envelope_errors 记录
+                .on_failure(()),
+        )
+        // 分配或沿用 X-Request-Id（在 TraceLayer 外层，span 才能读到）
+        .layer(axum::middleware::from_fn(common::request_id::request_id))
         .layer(cors)
         .with_state(state);
 
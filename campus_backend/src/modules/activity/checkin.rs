@@ -0,0 +1,123 @@
+// src/modules/activity/checkin.rs
+//
+// 活动签到码：按固定周期轮换的 HMAC 签名 token，举办方展示成二维码，
+// 参与者扫码后提交到 /activities/{id}/checkin。
+//
+// token 格式：`{window}.{signature}`
+// - window：当前时间所在的轮换窗口序号（unix 秒 / 周期）
+// - signature：HMAC-SHA256(secret, "checkin:{activity_id}:{window}")，base64url 编码
+//
+// 校验时接受当前窗口和上一个窗口，避免二维码刚好在扫码瞬间轮换导致失败。
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::common::secrets;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 签到码默认轮换周期（秒）
+const DEFAULT_ROTATE_SECONDS: i64 = 30;
+
+#[derive(Clone)]
+pub struct CheckinSigner {
+    secret: Vec<u8>,
+    /// 轮换周期（秒）
+    period: i64,
+}
+
+impl CheckinSigner {
+    pub fn new(secret: impl Into<Vec<u8>>, period: i64) -> Self {
+        Self {
+            secret: secret.into(),
+            period: period.max(1),
+        }
+    }
+
+    /// 读取 CHECKIN_SECRET（未配置时从 JWT_SECRET 派生）和 CHECKIN_ROTATE_SECONDS
+    pub fn from_env() -> Self {
+        let secret = secrets::signing_key("CHECKIN_SECRET", "activity-checkin");
+        let period = std::env::var("CHECKIN_ROTATE_SECONDS")
+            .map(|v| v.parse().expect("CHECKIN_ROTATE_SECONDS must be a number"))
+            .unwrap_or(DEFAULT_ROTATE_SECONDS);
+
+        Self::new(secret, period)
+    }
+
+    fn window_of(&self, now: DateTime<Utc>) -> i64 {
+        now.timestamp().div_euclid(self.period)
+    }
+
+    fn mac(&self, activity_id: i64, window: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts any key length");
+        mac.update(format!("checkin:{}:{}", activity_id, window).as_bytes());
+        mac
+    }
+
+    /// 生成当前窗口的签到码，返回 (token, 本窗口结束时间)
+    pub fn issue(&self, activity_id: i64, now: DateTime<Utc>) -> (String, DateTime<Utc>) {
+        let window = self.window_of(now);
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(activity_id, window).finalize().into_bytes());
+        let expire_at = Utc
+            .timestamp_opt((window + 1) * self.period, 0)
+            .single()
+            .unwrap_or(now);
+
+        (format!("{}.{}", window, signature), expire_at)
+    }
+
+    /// 校验签到码是否属于该活动，且处于当前或上一个窗口
+    pub fn verify(&self, activity_id: i64, token: &str, now: DateTime<Utc>) -> bool {
+        let Some((window, signature)) = token.trim().split_once('.') else {
+            return false;
+        };
+        let Ok(window) = window.parse::<i64>() else {
+            return false;
+        };
+        let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+            return false;
+        };
+
+        let current = self.window_of(now);
+        if window != current && window != current - 1 {
+            return false;
+        }
+
+        self.mac(activity_id, window).verify_slice(&signature).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_issue_and_verify() {
+        let signer = CheckinSigner::new("secret", 30);
+        let (token, expire_at) = signer.issue(7, at(1_000));
+
+        assert_eq!(expire_at, at(1_020));
+        assert!(signer.verify(7, &token, at(1_000)));
+        // 下一个窗口仍然可用（宽限一个周期）
+        assert!(signer.verify(7, &token, at(1_030)));
+        // 再往后就过期
+        assert!(!signer.verify(7, &token, at(1_050)));
+    }
+
+    #[test]
+    fn test_token_bound_to_activity_and_secret() {
+        let signer = CheckinSigner::new("secret", 30);
+        let (token, _) = signer.issue(7, at(1_000));
+
+        assert!(!signer.verify(8, &token, at(1_000)));
+        assert!(!CheckinSigner::new("other", 30).verify(7, &token, at(1_000)));
+        assert!(!signer.verify(7, "garbage", at(1_000)));
+    }
+}
@@ -0,0 +1,283 @@
+// src/modules/activity/controller.rs
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::{get, patch, post},
+    Json, Router,
+};
+use serde_json::json;
+
+use crate::common::{
+    auth::{Admin, AuthUser, OptionalAuthUser, RequireRole},
+    error::AppError,
+    response::ApiResponse,
+    state::AppState,
+};
+use crate::modules::activity::entity::*;
+use crate::modules::activity::export;
+
+type ApiResult<T> = Result<Json<ApiResponse<T>>, AppError>;
+
+/// 在 main.rs 里 merge 到总路由
+pub fn router() -> Router<AppState> {
+    Router::new()
+        // 辅助
+        .route("/api/v1/activity/categories", get(list_categories))
+        .route("/api/v1/activity/tags", get(list_tags))
+        // 公共活动
+        .route("/api/v1/activities", get(list_activities))
+        .route("/api/v1/activities/:id", get(get_activity_detail))
+        // 用户报名
+        .route("/api/v1/activities/:id/signup", post(signup_activity))
+        .route("/api/v1/activities/:id/cancel", post(cancel_signup))
+        .route("/api/v1/me/activities", get(list_my_activities))
+        // 举办方
+        .route("/api/v1/organizer/activities", post(create_activity))
+        .route("/api/v1/organizer/activities/:id", patch(update_activity))
+        .route("/api/v1/organizer/activities/:id/submit", post(submit_activity))
+        .route("/api/v1/organizer/activities/:id/withdraw", post(withdraw_activity))
+        .route("/api/v1/organizer/activities/:id/signups", get(list_signups))
+        .route("/api/v1/organizer/activities/:id/signups/export", get(export_signups))
+        // 签到
+        .route("/api/v1/organizer/activities/:id/checkin-code", get(get_checkin_code))
+        .route("/api/v1/activities/:id/checkin", post(checkin))
+        // 管理员
+        .route("/api/v1/admin/activities/:id/review", post(admin_review_activity))
+        .route("/api/v1/admin/activities/:id/block", post(admin_block_activity))
+}
+
+// ===== A. 辅助 API =====
+
+async fn list_categories(State(state): State<AppState>) -> ApiResult<Vec<serde_json::Value>> {
+    let items = state.activity.list_categories().await?;
+    let list = items
+        .into_iter()
+        .map(|(key, name)| json!({ "key": key, "name": name }))
+        .collect();
+    Ok(Json(ApiResponse::ok(list)))
+}
+
+async fn list_tags(
+    State(state): State<AppState>,
+    Query(query): Query<ListTagsQuery>,
+) -> ApiResult<Vec<serde_json::Value>> {
+    let items = state.activity.list_tags(query.keyword).await?;
+    let list = items
+        .into_iter()
+        .map(|(key, name)| json!({ "key": key, "name": name }))
+        .collect();
+    Ok(Json(ApiResponse::ok(list)))
+}
+
+// ===== B. 公共活动 API =====
+
+async fn list_activities(
+    State(state): State<AppState>,
+    OptionalAuthUser(current_user): OptionalAuthUser,
+    Query(query): Query<ListActivitiesQuery>,
+) -> ApiResult<Paged<ActivityListItem>> {
+    let res = state
+        .activity
+        .list_activities(current_user.as_ref(), query)
+        .await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn get_activity_detail(
+    State(state): State<AppState>,
+    OptionalAuthUser(current_user): OptionalAuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<ActivityDetail> {
+    let res = state
+        .activity
+        .get_activity_detail(current_user.as_ref(), id)
+        .await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+// ===== C. 用户报名相关 =====
+
+async fn signup_activity(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+    Json(body): Json<SignupActivityBody>,
+) -> ApiResult<ActivitySignup> {
+    let res = state.activity.signup_activity(&auth_user, id, body).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn cancel_signup(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<ActivitySignup> {
+    let res = state.activity.cancel_signup(&auth_user, id).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn list_my_activities(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ListMyActivitiesQuery>,
+) -> ApiResult<Paged<MyActivityItem>> {
+    let res = state.activity.list_my_activities(&auth_user, query).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+// ===== D. 举办方 API =====
+
+async fn create_activity(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(body): Json<CreateActivityBody>,
+) -> ApiResult<Activity> {
+    let res = state.activity.create_activity(&auth_user, body).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn update_activity(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+    Json(body): Json<UpdateActivityBody>,
+) -> ApiResult<Activity> {
+    let res = state.activity.update_activity(&auth_user, id, body).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn submit_activity(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<Activity> {
+    let res = state.activity.submit_activity(&auth_user, id).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn withdraw_activity(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<Activity> {
+    let res = state.activity.withdraw_activity(&auth_user, id).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn list_signups(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+    Query(query): Query<ListSignupsQuery>,
+) -> ApiResult<Paged<ActivitySignup>> {
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(20);
+
+    let res = state
+        .activity
+        .list_signups(&auth_user, id, page, page_size, query.status)
+        .await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+/// 导出报名表：?format=csv（默认，流式输出）或 ?format=xlsx
+async fn export_signups(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+    Query(query): Query<ExportSignupsQuery>,
+) -> Result<Response, AppError> {
+    let format = query.format.unwrap_or_else(|| "csv".to_string()).to_lowercase();
+    if format != "csv" && format != "xlsx" {
+        return Err(AppError::BadRequest("不支持的导出格式".into()));
+    }
+
+    let (fields, rows) = state.activity.export_signups(&auth_user, id).await?;
+    let disposition = format!("attachment; filename=\"activity-{}-signups.{}\"", id, format);
+
+    let response = if format == "xlsx" {
+        let bytes = export::xlsx_bytes(fields, rows).await?;
+        (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string(),
+                ),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            bytes,
+        )
+            .into_response()
+    } else {
+        (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            export::csv_body(fields, rows)?,
+        )
+            .into_response()
+    };
+
+    Ok(response)
+}
+
+// ===== E. 签到 API =====
+
+async fn get_checkin_code(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<serde_json::Value> {
+    let (token, expire_at) = state.activity.get_checkin_code(&auth_user, id).await?;
+    Ok(Json(ApiResponse::ok(json!({
+        "checkin_token": token,
+        "expire_at": expire_at,
+    }))))
+}
+
+async fn checkin(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+    Json(mut body): Json<CheckinBody>,
+) -> ApiResult<ActivitySignup> {
+    if body.device_info.is_none() {
+        body.device_info = headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+    }
+
+    let res = state.activity.checkin(&auth_user, id, body).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+// ===== F. 管理员审核 API =====
+
+async fn admin_review_activity(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(id): Path<i64>,
+    Json(body): Json<ReviewActivityBody>,
+) -> ApiResult<Activity> {
+    let res = state
+        .activity
+        .admin_review_activity(&admin, id, body)
+        .await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
+
+async fn admin_block_activity(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(id): Path<i64>,
+    body: Option<Json<BlockActivityBody>>,
+) -> ApiResult<Activity> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let res = state.activity.admin_block_activity(&admin, id, body).await?;
+    Ok(Json(ApiResponse::ok(res)))
+}
@@ -0,0 +1,354 @@
+// src/modules/activity/entity.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+
+use crate::modules::activity::form::{self, FormAnswers, FormField};
+
+/// 活动状态（MySQL ENUM）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivityStatus {
+    Draft,
+    PendingReview,
+    Published,
+    Finished,
+    Cancelled,
+}
+
+impl ActivityStatus {
+    /// 解析查询参数里的状态字符串（与 JSON/DB 中的写法一致）
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "DRAFT" => Some(Self::Draft),
+            "PENDING_REVIEW" => Some(Self::PendingReview),
+            "PUBLISHED" => Some(Self::Published),
+            "FINISHED" => Some(Self::Finished),
+            "CANCELLED" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// 可见范围（MySQL ENUM）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivityVisibility {
+    Public,
+    OrganizationOnly,
+    LinkOnly,
+}
+
+/// Activity 实体（既可映射 DB，也可作为对外 JSON）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Activity {
+    pub id: i64,
+    pub title: String,
+    pub cover_url: Option<String>,
+    pub summary: String,
+    pub description: String,
+    pub category: String,
+    pub tags: Json<Vec<String>>,
+    pub location: String,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub signup_start_time: Option<DateTime<Utc>>,
+    pub signup_end_time: Option<DateTime<Utc>>,
+    pub capacity: Option<i32>,
+    pub signup_count: i32,
+    pub organizer_id: String,
+    pub organizer_name: String,
+    pub organizer_type: String,
+    pub status: ActivityStatus,
+    pub visibility: ActivityVisibility,
+    pub can_comment: bool,
+    pub is_official: bool,
+    pub signup_required: bool,
+    pub checkin_required: bool,
+    /// 当前是否在报名窗口内（由生命周期定时任务维护）
+    pub signup_open: bool,
+    /// 自定义报名表单，未设置为 None
+    pub form_schema: Option<Json<Vec<FormField>>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted: bool,
+}
+
+/// 审核/状态流转动作（MySQL ENUM）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivityReviewAction {
+    Submit,
+    Withdraw,
+    Approve,
+    Reject,
+    Block,
+}
+
+/// 审核历史（activity_reviews 表）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityReview {
+    pub id: i64,
+    pub activity_id: i64,
+    /// 操作人：提交/撤回为举办方，审核/下线为管理员
+    pub reviewer_id: String,
+    pub action: ActivityReviewAction,
+    pub reason: Option<String>,
+    pub from_status: ActivityStatus,
+    pub to_status: ActivityStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 活动详情：举办方和管理员额外能看到审核历史和最近一次驳回原因
+#[derive(Debug, Serialize)]
+pub struct ActivityDetail {
+    #[serde(flatten)]
+    pub activity: Activity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reviews: Option<Vec<ActivityReview>>,
+}
+
+impl ActivityDetail {
+    pub fn public(activity: Activity) -> Self {
+        Self {
+            activity,
+            rejection_reason: None,
+            reviews: None,
+        }
+    }
+
+    /// reviews 按时间倒序；草稿最近一次流转是驳回时带出原因
+    pub fn with_reviews(activity: Activity, reviews: Vec<ActivityReview>) -> Self {
+        let rejection_reason = match reviews.first() {
+            Some(latest)
+                if activity.status == ActivityStatus::Draft
+                    && latest.action == ActivityReviewAction::Reject =>
+            {
+                latest.reason.clone()
+            }
+            _ => None,
+        };
+
+        Self {
+            activity,
+            rejection_reason,
+            reviews: Some(reviews),
+        }
+    }
+}
+
+/// 报名状态（MySQL ENUM）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivitySignupStatus {
+    Applied,
+    Cancelled,
+    CheckedIn,
+    /// 名额已满时进入候补，有人取消后按报名顺序递补
+    Waitlisted,
+}
+
+impl ActivitySignupStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Applied => "APPLIED",
+            Self::Cancelled => "CANCELLED",
+            Self::CheckedIn => "CHECKED_IN",
+            Self::Waitlisted => "WAITLISTED",
+        }
+    }
+}
+
+/// 报名记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivitySignup {
+    pub id: i64,
+    pub user_id: String,
+    pub activity_id: i64,
+    pub status: ActivitySignupStatus,
+    pub checkin_time: Option<DateTime<Utc>>,
+    /// 报名表单答案（已按 schema 校验）
+    pub form_answers: Option<Json<FormAnswers>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 报名表导出行（报名记录 + 报名人信息）
+#[derive(Debug, Clone, FromRow)]
+pub struct SignupExportRow {
+    pub username: Option<String>,
+    pub student_id: Option<String>,
+    pub college: Option<String>,
+    pub status: ActivitySignupStatus,
+    pub checkin_time: Option<DateTime<Utc>>,
+    pub form_answers: Option<Json<FormAnswers>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 签到记录（如需要单独返回）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityCheckin {
+    pub id: i64,
+    pub activity_id: i64,
+    pub user_id: String,
+    pub checkin_time: DateTime<Utc>,
+    pub method: String,
+    pub device_info: Option<String>,
+}
+
+/// 活动列表 item（可以直接复用 Activity，也可以做精简版）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityListItem {
+    pub id: i64,
+    pub title: String,
+    pub cover_url: Option<String>,
+    pub summary: String,
+    pub category: String,
+    pub tags: Json<Vec<String>>,
+    pub location: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub signup_end_time: Option<DateTime<Utc>>,
+    pub capacity: Option<i32>,
+    pub signup_count: i32,
+    pub status: ActivityStatus,
+    pub visibility: ActivityVisibility,
+    pub organizer_name: String,
+    pub is_official: bool,
+    pub signup_open: bool,
+}
+
+/// 我的活动列表 item：活动信息 + 我的报名状态
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MyActivityItem {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub activity: ActivityListItem,
+    pub signup_status: ActivitySignupStatus,
+    /// 候补时排在第几位（从 1 开始），非候补为 None
+    pub waitlist_position: Option<i64>,
+}
+
+/// 分页返回
+#[derive(Debug, Serialize)]
+pub struct Paged<T> {
+    pub total: i64,
+    pub list: Vec<T>,
+}
+
+/// ---------- 请求 DTO ----------
+
+#[derive(Debug, Deserialize)]
+pub struct ListActivitiesQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub keyword: Option<String>,
+    pub category: Option<String>,
+    pub status: Option<String>,
+    pub only_joined: Option<bool>,
+    pub start_time_from: Option<DateTime<Utc>>,
+    pub start_time_to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListMyActivitiesQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub filter: Option<String>, // upcoming / history
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListSignupsQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportSignupsQuery {
+    pub format: Option<String>, // csv（默认） / xlsx
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTagsQuery {
+    pub keyword: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignupActivityBody {
+    pub mobile: Option<String>,
+    pub student_id: Option<String>,
+    pub department: Option<String>,
+    /// 自定义表单答案：字段 key -> 答案（兼容旧字段名 extra_form，旧接口传的是 JSON 字符串）
+    #[serde(default, alias = "extra_form", deserialize_with = "form::deserialize_answers")]
+    pub form_answers: Option<FormAnswers>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateActivityBody {
+    pub title: String,
+    pub cover_url: Option<String>,
+    pub summary: String,
+    pub description: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub location: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub signup_start_time: Option<DateTime<Utc>>,
+    pub signup_end_time: Option<DateTime<Utc>>,
+    pub capacity: Option<i32>,
+    pub visibility: ActivityVisibility,
+    pub signup_required: Option<bool>,
+    pub checkin_required: Option<bool>,
+    pub can_comment: Option<bool>,
+    pub form_schema: Option<Vec<FormField>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateActivityBody {
+    pub title: Option<String>,
+    pub cover_url: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub location: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub signup_start_time: Option<DateTime<Utc>>,
+    pub signup_end_time: Option<DateTime<Utc>>,
+    pub capacity: Option<i32>,
+    pub visibility: Option<ActivityVisibility>,
+    pub signup_required: Option<bool>,
+    pub checkin_required: Option<bool>,
+    pub can_comment: Option<bool>,
+    pub form_schema: Option<Vec<FormField>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewActivityBody {
+    pub action: String, // APPROVE / REJECT
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BlockActivityBody {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckinBody {
+    pub checkin_token: String,
+    pub method: Option<String>,      // 默认 QR_CODE
+    pub device_info: Option<String>, // 未传时取 User-Agent
+}
@@ -0,0 +1,79 @@
+// src/modules/activity/events.rs
+//
+// 活动领域事件：每次状态流转成功（事务提交后）广播一条，
+// 通知等模块通过 ActivityEventBus::subscribe() 订阅。
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::modules::activity::entity::ActivityStatus;
+use crate::modules::activity::state_machine::ActivityTransition;
+
+/// 订阅者处理过慢时最多缓存的事件数，超出后慢订阅者会收到 Lagged
+const EVENT_BUFFER: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityEvent {
+    pub activity_id: i64,
+    pub transition: ActivityTransition,
+    pub from_status: ActivityStatus,
+    pub to_status: ActivityStatus,
+    /// 触发人；定时任务触发时为 None
+    pub actor_id: Option<String>,
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct ActivityEventBus {
+    sender: broadcast::Sender<ActivityEvent>,
+}
+
+impl ActivityEventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ActivityEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 发布事件；没有订阅者时直接丢弃
+    pub fn publish(&self, event: ActivityEvent) {
+        tracing::debug!(?event, "activity event");
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Default for ActivityEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_events() {
+        let bus = ActivityEventBus::new();
+        let mut rx = bus.subscribe();
+
+        bus.publish(ActivityEvent {
+            activity_id: 1,
+            transition: ActivityTransition::Approve,
+            from_status: ActivityStatus::PendingReview,
+            to_status: ActivityStatus::Published,
+            actor_id: Some("admin".into()),
+            reason: None,
+            occurred_at: Utc::now(),
+        });
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.activity_id, 1);
+        assert_eq!(event.to_status, ActivityStatus::Published);
+    }
+}
@@ -0,0 +1,209 @@
+// src/modules/activity/export.rs
+//
+// 报名表导出：CSV 逐行流式输出，XLSX 使用 constant_memory 模式逐行写入临时文件，
+// 两者都不会把全部报名记录一次性读进内存。
+// 单元格内容来自用户填写，以 = + - @ 等开头的值加 ' 前缀，防止被表格软件当成公式执行。
+
+use axum::body::{Body, Bytes};
+use futures_util::{StreamExt, TryStreamExt};
+use rust_xlsxwriter::{Workbook, XlsxError};
+use tokio::sync::mpsc;
+
+use crate::common::error::AppError;
+use crate::modules::activity::entity::SignupExportRow;
+use crate::modules::activity::form::{self, FormField};
+use crate::modules::activity::service::SignupExportStream;
+
+/// 固定列，表单字段按 schema 顺序追加在后面
+const FIXED_HEADERS: [&str; 6] = ["姓名", "学号", "学院", "报名状态", "签到时间", "报名时间"];
+
+/// UTF-8 BOM，保证 Excel 直接打开 CSV 时中文不乱码
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 数据库读取与 XLSX 写入线程之间最多缓冲的行数
+const XLSX_BUFFERED_ROWS: usize = 256;
+
+/// 表格软件会当成公式的开头字符
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// 以公式字符开头的单元格加 ' 前缀，按纯文本显示
+fn escape_formula(value: String) -> String {
+    if value.starts_with(FORMULA_PREFIXES) {
+        format!("'{}", value)
+    } else {
+        value
+    }
+}
+
+pub fn header_row(fields: &[FormField]) -> Vec<String> {
+    FIXED_HEADERS
+        .iter()
+        .map(|h| h.to_string())
+        .chain(fields.iter().map(|f| escape_formula(f.label.clone())))
+        .collect()
+}
+
+pub fn record_row(fields: &[FormField], row: &SignupExportRow) -> Vec<String> {
+    let mut record = vec![
+        row.username.clone().unwrap_or_default(),
+        row.student_id.clone().unwrap_or_default(),
+        row.college.clone().unwrap_or_default(),
+        row.status.as_str().to_string(),
+        row.checkin_time.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        row.created_at.to_rfc3339(),
+    ];
+    record.extend(form::answers_to_row(
+        fields,
+        row.form_answers.as_ref().map(|a| &a.0),
+    ));
+    record.into_iter().map(escape_formula).collect()
+}
+
+fn csv_line(record: &[String]) -> Result<Bytes, std::io::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(record)?;
+    writer
+        .into_inner()
+        .map(Bytes::from)
+        .map_err(|e| e.into_error())
+}
+
+/// CSV：先输出表头，之后每从数据库读到一行就编码一行
+pub fn csv_body(fields: Vec<FormField>, rows: SignupExportStream) -> Result<Body, AppError> {
+    let mut head = UTF8_BOM.to_vec();
+    head.extend_from_slice(&csv_line(&header_row(&fields)).map_err(export_err)?);
+
+    let lines = rows.map(move |row| match row {
+        Ok(row) => csv_line(&record_row(&fields, &row)),
+        Err(e) => Err(std::io::Error::other(e.to_string())),
+    });
+
+    let stream = futures_util::stream::once(async move { Ok(Bytes::from(head)) }).chain(lines);
+    Ok(Body::from_stream(stream))
+}
+
+/// XLSX：写文件和打包都是阻塞操作，放在 spawn_blocking 线程里；
+/// 当前任务只负责从数据库读行，经有界通道交给写入线程
+pub async fn xlsx_bytes(fields: Vec<FormField>, mut rows: SignupExportStream) -> Result<Vec<u8>, AppError> {
+    let (tx, rx) = mpsc::channel(XLSX_BUFFERED_ROWS);
+    let header = header_row(&fields);
+    let writer = tokio::task::spawn_blocking(move || write_xlsx(header, rx));
+
+    while let Some(row) = rows.try_next().await? {
+        // 写入线程提前退出说明已经出错，错误在下面取回
+        if tx.send(record_row(&fields, &row)).await.is_err() {
+            break;
+        }
+    }
+    drop(tx);
+
+    writer
+        .await
+        .map_err(|e| AppError::InternalError(format!("报名表导出失败: {}", e)))?
+}
+
+fn write_xlsx(header: Vec<String>, mut rows: mpsc::Receiver<Vec<String>>) -> Result<Vec<u8>, AppError> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet_with_constant_memory();
+    sheet.set_name("报名表").map_err(xlsx_err)?;
+
+    for (col, title) in header.iter().enumerate() {
+        sheet.write_string(0, col as u16, title).map_err(xlsx_err)?;
+    }
+
+    let mut row_idx: u32 = 1;
+    while let Some(record) = rows.blocking_recv() {
+        for (col, value) in record.iter().enumerate() {
+            sheet.write_string(row_idx, col as u16, value).map_err(xlsx_err)?;
+        }
+        row_idx += 1;
+    }
+
+    workbook.save_to_buffer().map_err(xlsx_err)
+}
+
+fn export_err(e: std::io::Error) -> AppError {
+    AppError::InternalError(format!("报名表导出失败: {}", e))
+}
+
+fn xlsx_err(e: XlsxError) -> AppError {
+    AppError::InternalError(format!("报名表导出失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::activity::entity::ActivitySignupStatus;
+    use serde_json::json;
+    use sqlx::types::Json;
+
+    #[test]
+    fn test_record_row_with_form_answers() {
+        let fields: Vec<FormField> = serde_json::from_value(json!([
+            { "key": "size", "label": "尺码", "type": "SINGLE_CHOICE", "options": ["S", "M"] }
+        ]))
+        .unwrap();
+
+        let row = SignupExportRow {
+            username: Some("张三".into()),
+            student_id: Some("2023001".into()),
+            college: None,
+            status: ActivitySignupStatus::CheckedIn,
+            checkin_time: None,
+            form_answers: Some(Json(serde_json::from_value(json!({ "size": "M" })).unwrap())),
+            created_at: chrono::DateTime::from_timestamp(0, 0).unwrap(),
+        };
+
+        assert_eq!(header_row(&fields).len(), 7);
+        assert_eq!(
+            record_row(&fields, &row),
+            vec!["张三", "2023001", "", "CHECKED_IN", "", "1970-01-01T00:00:00+00:00", "M"]
+        );
+        assert_eq!(
+            csv_line(&["a,b".to_string(), "c".to_string()]).unwrap(),
+            Bytes::from("\"a,b\",c\n")
+        );
+    }
+
+    #[test]
+    fn test_formula_cells_are_escaped() {
+        for (value, escaped) in [
+            ("=HYPERLINK(\"http://x\")", "'=HYPERLINK(\"http://x\")"),
+            ("+1", "'+1"),
+            ("-2+3", "'-2+3"),
+            ("@SUM(A1)", "'@SUM(A1)"),
+            ("\tcmd", "'\tcmd"),
+            ("\rcmd", "'\rcmd"),
+            ("张三", "张三"),
+            ("a=b", "a=b"),
+            ("", ""),
+        ] {
+            assert_eq!(escape_formula(value.to_string()), escaped);
+        }
+
+        let fields: Vec<FormField> = serde_json::from_value(json!([
+            { "key": "note", "label": "=备注", "type": "TEXT" }
+        ]))
+        .unwrap();
+        assert_eq!(header_row(&fields)[6], "'=备注");
+    }
+
+    #[tokio::test]
+    async fn test_xlsx_written_off_the_async_thread() {
+        let rows: SignupExportStream = Box::pin(futures_util::stream::iter((0..3).map(|i| {
+            Ok(SignupExportRow {
+                username: Some(format!("=user{}", i)),
+                student_id: None,
+                college: None,
+                status: ActivitySignupStatus::Applied,
+                checkin_time: None,
+                form_answers: None,
+                created_at: chrono::DateTime::from_timestamp(0, 0).unwrap(),
+            })
+        })));
+
+        let bytes = xlsx_bytes(Vec::new(), rows).await.unwrap();
+        // xlsx 是 zip 包
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
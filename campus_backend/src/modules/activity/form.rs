@@ -0,0 +1,319 @@
+// src/modules/activity/form.rs
+//
+// 活动自定义报名表单：举办方在创建活动时定义字段，报名时按字段校验答案。
+// schema 存在 activities.form_schema，答案存在 activity_signups.form_answers（均为 JSON 列）。
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// 文本类答案的最大长度
+const MAX_TEXT_LEN: usize = 500;
+
+/// 报名表单答案：字段 key -> 答案
+pub type FormAnswers = BTreeMap<String, Value>;
+
+/// 字段类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FormFieldKind {
+    Text,
+    SingleChoice,
+    MultiChoice,
+    Phone,
+    Email,
+}
+
+/// 表单字段定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormField {
+    pub key: String,
+    pub label: String,
+    #[serde(rename = "type")]
+    pub kind: FormFieldKind,
+    #[serde(default)]
+    pub required: bool,
+    /// 单选 / 多选的候选项
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+/// 校验举办方提交的 schema 本身是否合法
+pub fn validate_schema(fields: &[FormField]) -> Result<(), String> {
+    let mut keys = HashSet::new();
+
+    for field in fields {
+        if field.key.trim().is_empty() {
+            return Err("表单字段 key 不能为空".into());
+        }
+        if !keys.insert(field.key.as_str()) {
+            return Err(format!("表单字段 key 重复: {}", field.key));
+        }
+
+        match field.kind {
+            FormFieldKind::SingleChoice | FormFieldKind::MultiChoice => {
+                if field.options.is_empty() {
+                    return Err(format!("{}: 选择题至少需要一个选项", field.label));
+                }
+                let unique: HashSet<_> = field.options.iter().collect();
+                if unique.len() != field.options.len() {
+                    return Err(format!("{}: 选项重复", field.label));
+                }
+            }
+            _ => {
+                if !field.options.is_empty() {
+                    return Err(format!("{}: 只有选择题可以设置选项", field.label));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 按 schema 校验报名答案，返回清洗后的答案（去掉空值）
+pub fn validate_answers(fields: &[FormField], answers: &FormAnswers) -> Result<FormAnswers, String> {
+    if let Some(key) = answers.keys().find(|k| !fields.iter().any(|f| &f.key == *k)) {
+        return Err(format!("未知的表单字段: {}", key));
+    }
+
+    let mut cleaned = FormAnswers::new();
+
+    for field in fields {
+        let value = match answers.get(&field.key) {
+            Some(v) if !is_blank(v) => v,
+            _ => {
+                if field.required {
+                    return Err(format!("{}: 必填", field.label));
+                }
+                continue;
+            }
+        };
+
+        validate_value(field, value).map_err(|msg| format!("{}: {}", field.label, msg))?;
+        cleaned.insert(field.key.clone(), value.clone());
+    }
+
+    Ok(cleaned)
+}
+
+/// 把答案按 schema 顺序展开成一行文本（导出用），多选用 “;” 连接
+pub fn answers_to_row(fields: &[FormField], answers: Option<&FormAnswers>) -> Vec<String> {
+    fields
+        .iter()
+        .map(|field| match answers.and_then(|a| a.get(&field.key)) {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(";"),
+            Some(other) if !other.is_null() => other.to_string(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+/// 反序列化报名答案：既接受 JSON 对象，也接受旧接口 extra_form 的 JSON 字符串
+pub fn deserialize_answers<'de, D>(deserializer: D) -> Result<Option<FormAnswers>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Answers(FormAnswers),
+        Legacy(String),
+    }
+
+    match Option::<Raw>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Raw::Answers(answers)) => Ok(Some(answers)),
+        Some(Raw::Legacy(s)) if s.trim().is_empty() => Ok(None),
+        Some(Raw::Legacy(s)) => serde_json::from_str(&s)
+            .map(Some)
+            .map_err(|e| serde::de::Error::custom(format!("extra_form 不是合法的 JSON 对象: {}", e))),
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn validate_value(field: &FormField, value: &Value) -> Result<(), &'static str> {
+    match field.kind {
+        FormFieldKind::Text => {
+            let s = value.as_str().ok_or("应为文本")?;
+            if s.chars().count() > MAX_TEXT_LEN {
+                return Err("内容过长");
+            }
+        }
+        FormFieldKind::SingleChoice => {
+            let s = value.as_str().ok_or("应为单个选项")?;
+            if !field.options.iter().any(|o| o == s) {
+                return Err("不是有效的选项");
+            }
+        }
+        FormFieldKind::MultiChoice => {
+            let items = value.as_array().ok_or("应为选项列表")?;
+            let mut seen = HashSet::new();
+            for item in items {
+                let s = item.as_str().ok_or("应为选项列表")?;
+                if !field.options.iter().any(|o| o == s) {
+                    return Err("包含无效的选项");
+                }
+                if !seen.insert(s) {
+                    return Err("选项重复");
+                }
+            }
+        }
+        FormFieldKind::Phone => {
+            let s = value.as_str().ok_or("应为手机号")?;
+            if !is_phone(s) {
+                return Err("手机号格式不正确");
+            }
+        }
+        FormFieldKind::Email => {
+            let s = value.as_str().ok_or("应为邮箱")?;
+            if !is_email(s) {
+                return Err("邮箱格式不正确");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 大陆手机号：1 开头的 11 位数字
+fn is_phone(s: &str) -> bool {
+    s.len() == 11 && s.starts_with('1') && s.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_email(s: &str) -> bool {
+    if s.chars().any(char::is_whitespace) {
+        return false;
+    }
+    match s.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() >= 2
+                && domain.split('.').all(|part| !part.is_empty())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Vec<FormField> {
+        serde_json::from_value(json!([
+            { "key": "name", "label": "姓名", "type": "TEXT", "required": true },
+            { "key": "size", "label": "尺码", "type": "SINGLE_CHOICE", "options": ["S", "M", "L"] },
+            { "key": "days", "label": "可参加日期", "type": "MULTI_CHOICE", "options": ["周六", "周日"] },
+            { "key": "mobile", "label": "手机号", "type": "PHONE", "required": true },
+            { "key": "email", "label": "邮箱", "type": "EMAIL" }
+        ]))
+        .unwrap()
+    }
+
+    fn answers(value: Value) -> FormAnswers {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_valid_answers() {
+        let fields = schema();
+        assert!(validate_schema(&fields).is_ok());
+
+        let cleaned = validate_answers(
+            &fields,
+            &answers(json!({
+                "name": "张三",
+                "size": "M",
+                "days": ["周六"],
+                "mobile": "13800138000",
+                "email": ""
+            })),
+        )
+        .unwrap();
+
+        assert_eq!(cleaned.len(), 4);
+        assert!(!cleaned.contains_key("email"));
+    }
+
+    #[test]
+    fn test_invalid_answers() {
+        let fields = schema();
+        let base = json!({ "name": "张三", "mobile": "13800138000" });
+
+        assert!(validate_answers(&fields, &answers(json!({ "name": "张三" }))).is_err());
+
+        for (key, bad) in [
+            ("size", json!("XL")),
+            ("days", json!(["周六", "周六"])),
+            ("mobile", json!("12345")),
+            ("email", json!("a@b")),
+            ("unknown", json!("x")),
+        ] {
+            let mut a = answers(base.clone());
+            a.insert(key.to_string(), bad);
+            assert!(validate_answers(&fields, &a).is_err(), "{} should be rejected", key);
+        }
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        let dup: Vec<FormField> = serde_json::from_value(json!([
+            { "key": "a", "label": "A", "type": "TEXT" },
+            { "key": "a", "label": "B", "type": "TEXT" }
+        ]))
+        .unwrap();
+        assert!(validate_schema(&dup).is_err());
+
+        let no_options: Vec<FormField> = serde_json::from_value(json!([
+            { "key": "a", "label": "A", "type": "SINGLE_CHOICE" }
+        ]))
+        .unwrap();
+        assert!(validate_schema(&no_options).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_legacy_answers() {
+        #[derive(Deserialize)]
+        struct Body {
+            #[serde(default, alias = "extra_form", deserialize_with = "deserialize_answers")]
+            form_answers: Option<FormAnswers>,
+        }
+        let parse = |v: Value| serde_json::from_value::<Body>(v).map(|b| b.form_answers);
+
+        let expected = Some(answers(json!({ "size": "M" })));
+        assert_eq!(parse(json!({ "form_answers": { "size": "M" } })).unwrap(), expected);
+        assert_eq!(parse(json!({ "extra_form": { "size": "M" } })).unwrap(), expected);
+        assert_eq!(parse(json!({ "extra_form": "{\"size\":\"M\"}" })).unwrap(), expected);
+        assert_eq!(parse(json!({ "extra_form": "" })).unwrap(), None);
+        assert_eq!(parse(json!({ "extra_form": null })).unwrap(), None);
+        assert_eq!(parse(json!({})).unwrap(), None);
+        assert!(parse(json!({ "extra_form": "not json" })).is_err());
+        assert!(parse(json!({ "extra_form": "[1]" })).is_err());
+    }
+
+    #[test]
+    fn test_answers_to_row() {
+        let fields = schema();
+        let a = answers(json!({ "name": "张三", "days": ["周六", "周日"] }));
+        assert_eq!(
+            answers_to_row(&fields, Some(&a)),
+            vec!["张三", "", "周六;周日", "", ""]
+        );
+    }
+}
@@ -1,3 +1,11 @@
-pub mod service;
+pub mod checkin;
 pub mod controller;
-pub mod entity;
\ No newline at end of file
+pub mod entity;
+pub mod events;
+pub mod export;
+pub mod form;
+pub mod scheduler;
+pub mod service;
+pub mod state_machine;
+
+pub use controller::router;
@@ -0,0 +1,521 @@
+// src/modules/activity/scheduler.rs
+//
+// 活动生命周期定时任务：
+// - 已发布的活动过了 end_time 自动变为 FINISHED
+// - 按 signup_start_time / signup_end_time 打开、关闭报名（维护 signup_open 标记）
+//
+// 每一轮都是幂等的：只根据当前时间和行上的状态计算目标状态，UPDATE 带状态条件。
+// 多实例部署时用 MySQL 的 GET_LOCK 保证同一时刻只有一个实例在跑。
+// 读写活动经过 LifecycleStore，测试时用内存实现配合 FixedClock 驱动 run_once。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::pool::PoolConnection;
+use sqlx::{FromRow, MySql, MySqlPool};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::common::clock::Clock;
+use crate::modules::activity::entity::{Activity, ActivityStatus};
+use crate::modules::activity::events::{ActivityEvent, ActivityEventBus};
+use crate::modules::activity::state_machine::{self, ActivityTransition};
+
+/// MySQL 命名锁，所有实例共用
+const LOCK_NAME: &str = "campus_backend:activity_lifecycle";
+
+/// 默认每分钟跑一次
+const DEFAULT_INTERVAL_SECONDS: u64 = 60;
+
+/// 计算生命周期需要的字段
+#[derive(Debug, Clone, FromRow)]
+pub struct LifecycleRow {
+    pub id: i64,
+    pub status: ActivityStatus,
+    pub deleted: bool,
+    pub signup_required: bool,
+    pub signup_start_time: Option<DateTime<Utc>>,
+    pub signup_end_time: Option<DateTime<Utc>>,
+    pub end_time: DateTime<Utc>,
+    pub signup_open: bool,
+}
+
+impl From<&Activity> for LifecycleRow {
+    fn from(activity: &Activity) -> Self {
+        Self {
+            id: activity.id,
+            status: activity.status,
+            deleted: activity.deleted,
+            signup_required: activity.signup_required,
+            signup_start_time: activity.signup_start_time,
+            signup_end_time: activity.signup_end_time,
+            end_time: activity.end_time,
+            signup_open: activity.signup_open,
+        }
+    }
+}
+
+/// 单个活动本轮需要做的变更
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LifecycleChange {
+    /// 是否需要标记为 FINISHED（同时会关闭报名）
+    pub finish: bool,
+    /// 报名开关需要变成的值，None 表示不变
+    pub signup_open: Option<bool>,
+}
+
+/// 一轮执行的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LifecycleReport {
+    pub finished: u64,
+    pub signups_opened: u64,
+    pub signups_closed: u64,
+}
+
+impl LifecycleReport {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 活动此刻是否应当开放报名：已发布、未下线、未结束、需要报名且在报名时间内。
+/// 定时任务和状态流转（审核通过发布时）共用
+pub fn signup_should_open(row: &LifecycleRow, now: DateTime<Utc>) -> bool {
+    let published = !row.deleted && row.status == ActivityStatus::Published && now < row.end_time;
+    let started = row.signup_start_time.is_none_or(|start| start <= now);
+    let not_ended = row.signup_end_time.is_none_or(|end| now < end);
+    published && row.signup_required && started && not_ended
+}
+
+/// 根据当前时间计算活动的目标状态（纯函数，方便测试）
+pub fn plan(row: &LifecycleRow, now: DateTime<Utc>) -> LifecycleChange {
+    let finish = !row.deleted
+        && row.end_time <= now
+        && state_machine::next_status(row.status, ActivityTransition::Finish).is_some();
+
+    let should_open = !finish && signup_should_open(row, now);
+
+    LifecycleChange {
+        finish,
+        // FINISHED 的更新语句里已经关闭报名
+        signup_open: (!finish && should_open != row.signup_open).then_some(should_open),
+    }
+}
+
+/// 定时任务读写活动的接口：生产环境为 MySQL，测试时可以换成内存实现
+#[async_trait]
+pub trait LifecycleStore: Send + Sync {
+    /// 取得全局锁，返回持锁的会话；其他实例持有锁时返回 None
+    async fn lock(&self) -> Result<Option<Box<dyn LifecycleSession>>, sqlx::Error>;
+}
+
+/// 持有全局锁的一次会话，用完必须 release
+#[async_trait]
+pub trait LifecycleSession: Send {
+    /// 已发布或报名仍打开的活动
+    async fn candidates(&mut self) -> Result<Vec<LifecycleRow>, sqlx::Error>;
+    /// 已发布的活动标记为 FINISHED 并关闭报名；状态已被其他途径修改时返回 false
+    async fn finish(&mut self, activity_id: i64) -> Result<bool, sqlx::Error>;
+    /// 修改报名开关，返回是否真的改变了
+    async fn set_signup_open(&mut self, activity_id: i64, open: bool) -> Result<bool, sqlx::Error>;
+    async fn release(&mut self) -> Result<(), sqlx::Error>;
+}
+
+/// MySQL 实现：用 GET_LOCK 命名锁保证多实例下只有一个在跑
+pub struct MySqlLifecycleStore {
+    db: MySqlPool,
+}
+
+impl MySqlLifecycleStore {
+    pub fn new(db: MySqlPool) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl LifecycleStore for MySqlLifecycleStore {
+    async fn lock(&self) -> Result<Option<Box<dyn LifecycleSession>>, sqlx::Error> {
+        // GET_LOCK 是会话级的，加锁、执行、释放必须用同一个连接
+        let mut conn = self.db.acquire().await?;
+
+        let locked: Option<i64> = sqlx::query_scalar("SELECT GET_LOCK(?, 0)")
+            .bind(LOCK_NAME)
+            .fetch_one(&mut *conn)
+            .await?;
+        if locked != Some(1) {
+            return Ok(None);
+        }
+
+        Ok(Some(Box::new(MySqlLifecycleSession { conn })))
+    }
+}
+
+struct MySqlLifecycleSession {
+    conn: PoolConnection<MySql>,
+}
+
+#[async_trait]
+impl LifecycleSession for MySqlLifecycleSession {
+    async fn candidates(&mut self) -> Result<Vec<LifecycleRow>, sqlx::Error> {
+        sqlx::query_as::<_, LifecycleRow>(
+            r#"
+            SELECT id, status, deleted, signup_required, signup_start_time, signup_end_time,
+                   end_time, signup_open
+            FROM activities
+            WHERE status = 'PUBLISHED' OR signup_open = TRUE
+            "#,
+        )
+        .fetch_all(&mut *self.conn)
+        .await
+    }
+
+    async fn finish(&mut self, activity_id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE activities
+            SET status = 'FINISHED',
+                signup_open = FALSE,
+                updated_at = NOW()
+            WHERE id = ? AND status = 'PUBLISHED'
+            "#,
+        )
+        .bind(activity_id)
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_signup_open(&mut self, activity_id: i64, open: bool) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE activities
+            SET signup_open = ?,
+                updated_at = NOW()
+            WHERE id = ? AND signup_open <> ?
+            "#,
+        )
+        .bind(open)
+        .bind(activity_id)
+        .bind(open)
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn release(&mut self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT RELEASE_LOCK(?)")
+            .bind(LOCK_NAME)
+            .execute(&mut *self.conn)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct LifecycleScheduler {
+    store: Arc<dyn LifecycleStore>,
+    clock: Arc<dyn Clock>,
+    interval: Duration,
+    events: ActivityEventBus,
+}
+
+impl LifecycleScheduler {
+    pub fn new(
+        store: Arc<dyn LifecycleStore>,
+        clock: Arc<dyn Clock>,
+        interval: Duration,
+        events: ActivityEventBus,
+    ) -> Self {
+        Self { store, clock, interval, events }
+    }
+
+    /// 使用 MySQL，读取 ACTIVITY_SCHEDULER_INTERVAL（秒）
+    pub fn from_env(db: MySqlPool, clock: Arc<dyn Clock>, events: ActivityEventBus) -> Self {
+        let seconds = std::env::var("ACTIVITY_SCHEDULER_INTERVAL")
+            .map(|v| v.parse().expect("ACTIVITY_SCHEDULER_INTERVAL must be a number"))
+            .unwrap_or(DEFAULT_INTERVAL_SECONDS);
+
+        Self::new(
+            Arc::new(MySqlLifecycleStore::new(db)),
+            clock,
+            Duration::from_secs(seconds.max(1)),
+            events,
+        )
+    }
+
+    /// 在后台循环执行
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+
+                match self.run_once().await {
+                    Ok(Some(report)) if !report.is_empty() => {
+                        tracing::info!(?report, "activity lifecycle updated");
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("activity lifecycle failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// 执行一轮；其他实例持有锁时返回 None
+    pub async fn run_once(&self) -> Result<Option<LifecycleReport>, sqlx::Error> {
+        let Some(mut session) = self.store.lock().await? else {
+            return Ok(None);
+        };
+
+        let result = self.apply(session.as_mut(), self.clock.now()).await;
+        session.release().await?;
+
+        result.map(Some)
+    }
+
+    async fn apply(
+        &self,
+        session: &mut dyn LifecycleSession,
+        now: DateTime<Utc>,
+    ) -> Result<LifecycleReport, sqlx::Error> {
+        let rows = session.candidates().await?;
+        let mut report = LifecycleReport::default();
+
+        for row in &rows {
+            let change = plan(row, now);
+
+            // 其他途径已经改过状态时不重复发事件
+            if change.finish && session.finish(row.id).await? {
+                report.finished += 1;
+                self.events.publish(ActivityEvent {
+                    activity_id: row.id,
+                    transition: ActivityTransition::Finish,
+                    from_status: row.status,
+                    to_status: ActivityStatus::Finished,
+                    actor_id: None,
+                    reason: None,
+                    occurred_at: now,
+                });
+            }
+
+            if let Some(open) = change.signup_open {
+                if session.set_signup_open(row.id, open).await? {
+                    if open {
+                        report.signups_opened += 1;
+                    } else {
+                        report.signups_closed += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::clock::FixedClock;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    /// 内存里的活动表
+    #[derive(Clone, Default)]
+    struct MemoryStore {
+        rows: Arc<Mutex<Vec<LifecycleRow>>>,
+        locked: Arc<Mutex<bool>>,
+    }
+
+    #[async_trait]
+    impl LifecycleStore for MemoryStore {
+        async fn lock(&self) -> Result<Option<Box<dyn LifecycleSession>>, sqlx::Error> {
+            let mut locked = self.locked.lock().unwrap();
+            if *locked {
+                return Ok(None);
+            }
+            *locked = true;
+            Ok(Some(Box::new(self.clone())))
+        }
+    }
+
+    #[async_trait]
+    impl LifecycleSession for MemoryStore {
+        async fn candidates(&mut self) -> Result<Vec<LifecycleRow>, sqlx::Error> {
+            let rows = self.rows.lock().unwrap();
+            Ok(rows
+                .iter()
+                .filter(|r| r.status == ActivityStatus::Published || r.signup_open)
+                .cloned()
+                .collect())
+        }
+
+        async fn finish(&mut self, activity_id: i64) -> Result<bool, sqlx::Error> {
+            let mut rows = self.rows.lock().unwrap();
+            let row = rows
+                .iter_mut()
+                .find(|r| r.id == activity_id && r.status == ActivityStatus::Published);
+            Ok(row
+                .map(|r| {
+                    r.status = ActivityStatus::Finished;
+                    r.signup_open = false;
+                })
+                .is_some())
+        }
+
+        async fn set_signup_open(
+            &mut self,
+            activity_id: i64,
+            open: bool,
+        ) -> Result<bool, sqlx::Error> {
+            let mut rows = self.rows.lock().unwrap();
+            let row = rows
+                .iter_mut()
+                .find(|r| r.id == activity_id && r.signup_open != open);
+            Ok(row.map(|r| r.signup_open = open).is_some())
+        }
+
+        async fn release(&mut self) -> Result<(), sqlx::Error> {
+            *self.locked.lock().unwrap() = false;
+            Ok(())
+        }
+    }
+
+    fn scheduler(
+        store: &MemoryStore,
+        now: DateTime<Utc>,
+        events: &ActivityEventBus,
+    ) -> LifecycleScheduler {
+        LifecycleScheduler::new(
+            Arc::new(store.clone()),
+            Arc::new(FixedClock(now)),
+            Duration::from_secs(60),
+            events.clone(),
+        )
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, hour, 0, 0).unwrap()
+    }
+
+    fn row() -> LifecycleRow {
+        LifecycleRow {
+            id: 1,
+            status: ActivityStatus::Published,
+            deleted: false,
+            signup_required: true,
+            signup_start_time: Some(at(8)),
+            signup_end_time: Some(at(12)),
+            end_time: at(18),
+            signup_open: false,
+        }
+    }
+
+    #[test]
+    fn test_signup_window_opens_and_closes() {
+        let clock = FixedClock(at(7));
+        assert_eq!(plan(&row(), clock.now()), LifecycleChange::default());
+
+        let clock = FixedClock(at(9));
+        assert_eq!(plan(&row(), clock.now()).signup_open, Some(true));
+
+        // 已经打开的不重复更新
+        let open = LifecycleRow { signup_open: true, ..row() };
+        assert_eq!(plan(&open, clock.now()), LifecycleChange::default());
+
+        let clock = FixedClock(at(12));
+        assert_eq!(plan(&open, clock.now()).signup_open, Some(false));
+    }
+
+    #[test]
+    fn test_finish_after_end_time() {
+        let open = LifecycleRow { signup_open: true, ..row() };
+        let change = plan(&open, FixedClock(at(18)).now());
+        assert!(change.finish);
+        assert_eq!(change.signup_open, None);
+
+        let finished = LifecycleRow { status: ActivityStatus::Finished, ..row() };
+        assert_eq!(plan(&finished, at(20)), LifecycleChange::default());
+    }
+
+    #[tokio::test]
+    async fn test_run_once_follows_injected_clock() {
+        let store = MemoryStore::default();
+        store.rows.lock().unwrap().push(row());
+        let events = ActivityEventBus::new();
+        let mut received = events.subscribe();
+
+        // 报名开始前什么都不做
+        let report = scheduler(&store, at(7), &events).run_once().await.unwrap();
+        assert_eq!(report, Some(LifecycleReport::default()));
+
+        let report = scheduler(&store, at(9), &events)
+            .run_once()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.signups_opened, 1);
+        assert!(store.rows.lock().unwrap()[0].signup_open);
+
+        let report = scheduler(&store, at(12), &events)
+            .run_once()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.signups_closed, 1);
+
+        let report = scheduler(&store, at(18), &events)
+            .run_once()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.finished, 1);
+        assert_eq!(
+            store.rows.lock().unwrap()[0].status,
+            ActivityStatus::Finished
+        );
+
+        let event = received.try_recv().unwrap();
+        assert_eq!(
+            (event.activity_id, event.to_status, event.occurred_at),
+            (1, ActivityStatus::Finished, at(18))
+        );
+
+        // 锁被占用时跳过本轮
+        *store.locked.lock().unwrap() = true;
+        assert_eq!(
+            scheduler(&store, at(19), &events).run_once().await.unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_signup_opens_on_publish() {
+        let now = at(9);
+        let pending = LifecycleRow { status: ActivityStatus::PendingReview, ..row() };
+        assert!(!signup_should_open(&pending, now));
+        assert!(signup_should_open(
+            &LifecycleRow { status: ActivityStatus::Published, ..pending.clone() },
+            now
+        ));
+        assert!(!signup_should_open(
+            &LifecycleRow { signup_required: false, ..row() },
+            now
+        ));
+        assert!(!signup_should_open(&row(), at(18)));
+    }
+
+    #[test]
+    fn test_blocked_or_unpublished_closes_signup() {
+        let blocked = LifecycleRow { deleted: true, signup_open: true, ..row() };
+        assert_eq!(plan(&blocked, at(9)).signup_open, Some(false));
+
+        let draft = LifecycleRow { status: ActivityStatus::Draft, ..row() };
+        assert_eq!(plan(&draft, at(9)), LifecycleChange::default());
+    }
+}
@@ -0,0 +1,1474 @@
+// src/modules/activity/service.rs
+
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use futures_util::TryStreamExt;
+use sqlx::types::Json;
+use sqlx::{MySql, MySqlConnection, MySqlPool, QueryBuilder};
+
+use crate::common::auth::AuthUser;
+use crate::common::error::AppError;
+use crate::modules::activity::checkin::CheckinSigner;
+use crate::modules::activity::form::{self, FormAnswers, FormField};
+use crate::modules::activity::entity::*;
+use crate::modules::activity::events::{ActivityEvent, ActivityEventBus};
+use crate::modules::activity::scheduler::{self, LifecycleRow};
+use crate::modules::activity::state_machine::{self, ActivityTransition};
+
+pub type ServiceResult<T> = Result<T, ServiceError>;
+
+/// 报名表导出的逐行数据流
+pub type SignupExportStream = BoxStream<'static, ServiceResult<SignupExportRow>>;
+
+#[derive(Debug)]
+pub enum ServiceError {
+    NotFound,
+    Unauthorized,
+    PermissionDenied,
+    BadRequest(String),
+    /// 与当前状态冲突（重复报名、重复签到等）
+    Conflict(String),
+    Db(sqlx::Error),
+    Internal,
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::NotFound => write!(f, "not found"),
+            ServiceError::Unauthorized => write!(f, "unauthorized"),
+            ServiceError::PermissionDenied => write!(f, "permission denied"),
+            ServiceError::BadRequest(msg) => write!(f, "bad request: {}", msg),
+            ServiceError::Conflict(msg) => write!(f, "conflict: {}", msg),
+            ServiceError::Db(e) => write!(f, "db error: {}", e),
+            ServiceError::Internal => write!(f, "internal error"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<sqlx::Error> for ServiceError {
+    fn from(err: sqlx::Error) -> Self {
+        ServiceError::Db(err)
+    }
+}
+
+/// 活动模块的业务错误统一转换成 AppError 返回给客户端
+impl From<ServiceError> for AppError {
+    fn from(err: ServiceError) -> Self {
+        match err {
+            ServiceError::NotFound => AppError::NotFound("活动不存在或无权访问".to_string()),
+            ServiceError::Unauthorized => AppError::Unauthorized("请先登录".to_string()),
+            ServiceError::PermissionDenied => AppError::Forbidden("权限不足".to_string()),
+            ServiceError::BadRequest(msg) => AppError::BadRequest(msg),
+            ServiceError::Conflict(msg) => AppError::Conflict(msg),
+            ServiceError::Db(e) => AppError::DatabaseError(e),
+            ServiceError::Internal => AppError::InternalError("服务器内部错误".to_string()),
+        }
+    }
+}
+
+fn is_admin(user: &AuthUser) -> bool {
+    user.role == "admin"
+}
+
+/// 报名请求里的 mobile / student_id / department 是常用字段的快捷写法：
+/// 表单里定义了同名字段、且答案里没填时合并进答案
+fn merge_builtin_answers(fields: &[FormField], body: SignupActivityBody) -> FormAnswers {
+    let mut answers = body.form_answers.unwrap_or_default();
+
+    for (key, value) in [
+        ("mobile", body.mobile),
+        ("student_id", body.student_id),
+        ("department", body.department),
+    ] {
+        if let Some(value) = value {
+            if fields.iter().any(|f| f.key == key) && !answers.contains_key(key) {
+                answers.insert(key.to_string(), serde_json::Value::String(value));
+            }
+        }
+    }
+
+    answers
+}
+
+/// 活动当前是否接受报名：只有已发布、需要报名、在报名时间内且报名开关打开的活动可以报名
+fn ensure_signup_allowed(activity: &Activity, now: chrono::DateTime<chrono::Utc>) -> ServiceResult<()> {
+    match activity.status {
+        ActivityStatus::Published => {}
+        ActivityStatus::Draft | ActivityStatus::PendingReview => {
+            return Err(ServiceError::Conflict("活动尚未发布，不能报名".into()));
+        }
+        ActivityStatus::Finished => return Err(ServiceError::Conflict("活动已结束".into())),
+        ActivityStatus::Cancelled => return Err(ServiceError::Conflict("活动已取消".into())),
+    }
+
+    if !activity.signup_required {
+        return Err(ServiceError::BadRequest("该活动不需要报名".into()));
+    }
+
+    if let Some(start) = activity.signup_start_time {
+        if now < start {
+            return Err(ServiceError::BadRequest("报名尚未开始".into()));
+        }
+    }
+    if let Some(end) = activity.signup_end_time {
+        if now > end {
+            return Err(ServiceError::BadRequest("报名已经结束".into()));
+        }
+    }
+
+    // signup_open 由生命周期定时任务维护，关闭时不接受报名
+    if !activity.signup_open {
+        return Err(ServiceError::Conflict("报名未开放".into()));
+    }
+    Ok(())
+}
+
+/// 活动列表的 WHERE 条件（总数和列表共用）
+///
+/// 可见性规则：
+/// - 举办方总能看到自己的活动（包括草稿、审核中、仅链接可见）
+/// - 其他人只能看到非草稿/非审核中的活动，未登录只看 PUBLIC，登录后额外可见 ORGANIZATION_ONLY
+/// - LINK_ONLY 不出现在列表里，只能通过详情链接访问
+fn push_list_filters(
+    qb: &mut QueryBuilder<'_, MySql>,
+    current_user: Option<&AuthUser>,
+    query: &ListActivitiesQuery,
+    status: Option<ActivityStatus>,
+) {
+    qb.push(" WHERE a.deleted = FALSE");
+
+    match current_user {
+        Some(user) => {
+            qb.push(
+                " AND ((a.status NOT IN ('DRAFT', 'PENDING_REVIEW') \
+                 AND a.visibility IN ('PUBLIC', 'ORGANIZATION_ONLY')) \
+                 OR a.organizer_id = ",
+            )
+            .push_bind(user.user_id.clone())
+            .push(")");
+        }
+        None => {
+            qb.push(" AND a.status NOT IN ('DRAFT', 'PENDING_REVIEW') AND a.visibility = 'PUBLIC'");
+        }
+    }
+
+    if let Some(kw) = query.keyword.as_deref().map(str::trim).filter(|kw| !kw.is_empty()) {
+        let pattern = format!("%{}%", kw);
+        qb.push(" AND (a.title LIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR a.summary LIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+    if let Some(category) = query.category.as_deref().filter(|c| !c.is_empty()) {
+        qb.push(" AND a.category = ").push_bind(category.to_string());
+    }
+    if let Some(status) = status {
+        qb.push(" AND a.status = ").push_bind(status);
+    }
+    if let (Some(true), Some(user)) = (query.only_joined, current_user) {
+        qb.push(
+            " AND EXISTS (SELECT 1 FROM activity_signups s \
+             WHERE s.activity_id = a.id AND s.status IN ('APPLIED', 'CHECKED_IN') AND s.user_id = ",
+        )
+        .push_bind(user.user_id.clone())
+        .push(")");
+    }
+    if let Some(from) = query.start_time_from {
+        qb.push(" AND a.start_time >= ").push_bind(from);
+    }
+    if let Some(to) = query.start_time_to {
+        qb.push(" AND a.start_time <= ").push_bind(to);
+    }
+}
+
+/// activities 表的完整列，SELECT 时统一使用，保证能映射到 Activity
+const ACTIVITY_COLUMNS: &str = r#"
+    id, title, cover_url, summary, description, category, tags, location,
+    longitude, latitude, start_time, end_time, signup_start_time, signup_end_time,
+    capacity, signup_count, organizer_id, organizer_name, organizer_type,
+    status, visibility, can_comment, is_official, signup_required, checkin_required,
+    signup_open, form_schema, created_at, updated_at, deleted
+"#;
+
+const SIGNUP_COLUMNS: &str =
+    "id, user_id, activity_id, status, checkin_time, form_answers, created_at, updated_at";
+
+#[async_trait]
+pub trait ActivityService: Send + Sync + 'static {
+    // 分类 / 标签
+    async fn list_categories(&self) -> ServiceResult<Vec<(String, String)>>;
+    async fn list_tags(&self, keyword: Option<String>) -> ServiceResult<Vec<(String, String)>>;
+
+    // 公共活动
+    async fn list_activities(
+        &self,
+        current_user: Option<&AuthUser>,
+        query: ListActivitiesQuery,
+    ) -> ServiceResult<Paged<ActivityListItem>>;
+
+    async fn get_activity_detail(
+        &self,
+        current_user: Option<&AuthUser>,
+        activity_id: i64,
+    ) -> ServiceResult<ActivityDetail>;
+
+    // 用户报名相关
+    async fn signup_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        body: SignupActivityBody,
+    ) -> ServiceResult<ActivitySignup>;
+
+    async fn cancel_signup(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<ActivitySignup>;
+
+    async fn list_my_activities(
+        &self,
+        user: &AuthUser,
+        query: ListMyActivitiesQuery,
+    ) -> ServiceResult<Paged<MyActivityItem>>;
+
+    // 举办方 CRUD
+    async fn create_activity(
+        &self,
+        user: &AuthUser,
+        body: CreateActivityBody,
+    ) -> ServiceResult<Activity>;
+
+    async fn update_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        body: UpdateActivityBody,
+    ) -> ServiceResult<Activity>;
+
+    async fn submit_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<Activity>;
+
+    async fn withdraw_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<Activity>;
+
+    async fn list_signups(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        page: i64,
+        page_size: i64,
+        status: Option<String>,
+    ) -> ServiceResult<Paged<ActivitySignup>>;
+
+    /// 导出全部报名记录及表单答案（举办方 / 管理员）
+    async fn export_signups(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<(Vec<FormField>, SignupExportStream)>;
+
+    // 签到
+    async fn get_checkin_code(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<(String, chrono::DateTime<chrono::Utc>)>;
+
+    async fn checkin(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        body: CheckinBody,
+    ) -> ServiceResult<ActivitySignup>;
+
+    // 管理员
+    async fn admin_review_activity(
+        &self,
+        admin: &AuthUser,
+        activity_id: i64,
+        body: ReviewActivityBody,
+    ) -> ServiceResult<Activity>;
+
+    async fn admin_block_activity(
+        &self,
+        admin: &AuthUser,
+        activity_id: i64,
+        body: BlockActivityBody,
+    ) -> ServiceResult<Activity>;
+}
+
+/// 具体实现
+pub struct ActivityServiceImpl {
+    pub db: MySqlPool,
+    /// 签到码签名器
+    pub checkin: CheckinSigner,
+    /// 状态流转事件
+    pub events: ActivityEventBus,
+}
+
+impl ActivityServiceImpl {
+    pub fn new(db: MySqlPool, checkin: CheckinSigner, events: ActivityEventBus) -> Arc<Self> {
+        Arc::new(Self { db, checkin, events })
+    }
+
+    /// 按 id 查活动（MySQL 没有 RETURNING，写操作之后也用它回读）
+    async fn fetch_activity(
+        conn: &mut MySqlConnection,
+        activity_id: i64,
+        for_update: bool,
+    ) -> ServiceResult<Activity> {
+        let sql = format!(
+            "SELECT {} FROM activities WHERE id = ?{}",
+            ACTIVITY_COLUMNS,
+            if for_update { " FOR UPDATE" } else { "" }
+        );
+
+        sqlx::query_as::<_, Activity>(&sql)
+            .bind(activity_id)
+            .fetch_optional(conn)
+            .await?
+            .ok_or(ServiceError::NotFound)
+    }
+
+    async fn fetch_signup(conn: &mut MySqlConnection, signup_id: i64) -> ServiceResult<ActivitySignup> {
+        let sql = format!("SELECT {} FROM activity_signups WHERE id = ?", SIGNUP_COLUMNS);
+
+        sqlx::query_as::<_, ActivitySignup>(&sql)
+            .bind(signup_id)
+            .fetch_optional(conn)
+            .await?
+            .ok_or(ServiceError::NotFound)
+    }
+
+    /// 按状态机执行一次流转：校验、更新状态、写审核历史。
+    /// 返回的事件要在事务提交后再发布
+    async fn apply_transition(
+        conn: &mut MySqlConnection,
+        activity: &Activity,
+        transition: ActivityTransition,
+        actor: &AuthUser,
+        reason: Option<&str>,
+    ) -> ServiceResult<ActivityEvent> {
+        let to_status = state_machine::transition(activity.status, transition)
+            .map_err(ServiceError::BadRequest)?;
+
+        // 报名开关随状态一起更新：审核通过时已在报名时间内的活动立即开放报名，
+        // 不用等下一轮定时任务；取消、下线等则立即关闭
+        let blocked = transition == ActivityTransition::Block;
+        let target = LifecycleRow {
+            status: to_status,
+            deleted: activity.deleted || blocked,
+            ..LifecycleRow::from(activity)
+        };
+        let signup_open = scheduler::signup_should_open(&target, chrono::Utc::now());
+
+        // 下线同时标记 deleted，不再出现在任何列表里
+        let sql = if blocked {
+            "UPDATE activities SET status = ?, signup_open = ?, deleted = TRUE, updated_at = NOW() WHERE id = ?"
+        } else {
+            "UPDATE activities SET status = ?, signup_open = ?, updated_at = NOW() WHERE id = ?"
+        };
+        sqlx::query(sql)
+            .bind(to_status)
+            .bind(signup_open)
+            .bind(activity.id)
+            .execute(&mut *conn)
+            .await?;
+
+        if let Some(action) = transition.review_action() {
+            Self::record_review(conn, activity.id, actor, action, reason, activity.status, to_status)
+                .await?;
+        }
+
+        Ok(ActivityEvent {
+            activity_id: activity.id,
+            transition,
+            from_status: activity.status,
+            to_status,
+            actor_id: Some(actor.user_id.clone()),
+            reason: reason.map(str::to_string),
+            occurred_at: chrono::Utc::now(),
+        })
+    }
+
+    /// 记录一条审核/状态流转历史
+    async fn record_review(
+        conn: &mut MySqlConnection,
+        activity_id: i64,
+        reviewer: &AuthUser,
+        action: ActivityReviewAction,
+        reason: Option<&str>,
+        from_status: ActivityStatus,
+        to_status: ActivityStatus,
+    ) -> ServiceResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO activity_reviews (
+                activity_id, reviewer_id, action, reason, from_status, to_status, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, NOW())
+            "#,
+        )
+        .bind(activity_id)
+        .bind(&reviewer.user_id)
+        .bind(action)
+        .bind(reason)
+        .bind(from_status)
+        .bind(to_status)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 查询活动的审核历史（按时间倒序）
+    async fn fetch_reviews(
+        conn: &mut MySqlConnection,
+        activity_id: i64,
+    ) -> ServiceResult<Vec<ActivityReview>> {
+        let reviews = sqlx::query_as::<_, ActivityReview>(
+            r#"
+            SELECT id, activity_id, reviewer_id, action, reason, from_status, to_status, created_at
+            FROM activity_reviews
+            WHERE activity_id = ?
+            ORDER BY id DESC
+            "#,
+        )
+        .bind(activity_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(reviews)
+    }
+}
+
+#[async_trait]
+impl ActivityService for ActivityServiceImpl {
+    async fn list_categories(&self) -> ServiceResult<Vec<(String, String)>> {
+        // TODO: 从配置表/枚举表里查
+        Ok(vec![
+            ("lecture".into(), "讲座/分享".into()),
+            ("club".into(), "社团活动".into()),
+            ("volunteer".into(), "志愿服务".into()),
+        ])
+    }
+
+    async fn list_tags(&self, keyword: Option<String>) -> ServiceResult<Vec<(String, String)>> {
+        // 默认匹配全部
+        let pattern = match keyword {
+            Some(kw) if !kw.trim().is_empty() => format!("%{}%", kw.trim()),
+            _ => "%".to_string(),
+        };
+
+        // 查询标签（MySQL 默认排序规则下 LIKE 不区分大小写）
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT `key`, name
+            FROM activity_tags
+            WHERE `key` LIKE ? OR name LIKE ?
+            ORDER BY name ASC
+            "#,
+        )
+        .bind(&pattern)
+        .bind(&pattern)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn list_activities(
+        &self,
+        current_user: Option<&AuthUser>,
+        query: ListActivitiesQuery,
+    ) -> ServiceResult<Paged<ActivityListItem>> {
+        // 1. 处理分页参数
+        let page = query.page.unwrap_or(1).max(1);
+        let mut page_size = query.page_size.unwrap_or(10);
+        if page_size > 50 {
+            page_size = 50;
+        } else if page_size <= 0 {
+            page_size = 10;
+        }
+        let offset = (page - 1) * page_size;
+
+        // 2. 校验筛选参数
+        let status = match query.status.as_deref() {
+            Some(s) if !s.trim().is_empty() => Some(
+                ActivityStatus::parse(s.trim())
+                    .ok_or_else(|| ServiceError::BadRequest("无效的活动状态筛选".into()))?,
+            ),
+            _ => None,
+        };
+        if query.only_joined == Some(true) && current_user.is_none() {
+            return Err(ServiceError::Unauthorized);
+        }
+
+        // 3. 先查总数
+        let mut count_qb: QueryBuilder<MySql> =
+            QueryBuilder::new("SELECT COUNT(*) FROM activities a");
+        push_list_filters(&mut count_qb, current_user, &query, status);
+
+        let total: i64 = count_qb.build_query_scalar().fetch_one(&self.db).await?;
+
+        // 4. 查列表（精简版字段映射到 ActivityListItem）
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new(
+            r#"
+            SELECT
+                a.id, a.title, a.cover_url, a.summary, a.category, a.tags, a.location,
+                a.start_time, a.end_time, a.signup_end_time, a.capacity, a.signup_count,
+                a.status, a.visibility, a.organizer_name, a.is_official, a.signup_open
+            FROM activities a
+            "#,
+        );
+        push_list_filters(&mut qb, current_user, &query, status);
+        qb.push(" ORDER BY a.start_time DESC LIMIT ")
+            .push_bind(page_size)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let list = qb
+            .build_query_as::<ActivityListItem>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(Paged { total, list })
+    }
+
+    async fn get_activity_detail(
+        &self,
+        current_user: Option<&AuthUser>,
+        activity_id: i64,
+    ) -> ServiceResult<ActivityDetail> {
+        let mut conn = self.db.acquire().await?;
+        let activity = Self::fetch_activity(&mut conn, activity_id, false).await?;
+
+        // 举办方和管理员总能看到，并附带审核历史；其他人按状态和可见范围判断
+        let privileged = current_user
+            .map(|u| u.user_id == activity.organizer_id || is_admin(u))
+            .unwrap_or(false);
+        if privileged {
+            let reviews = Self::fetch_reviews(&mut conn, activity_id).await?;
+            return Ok(ActivityDetail::with_reviews(activity, reviews));
+        }
+
+        let hidden = activity.deleted
+            || matches!(
+                activity.status,
+                ActivityStatus::Draft | ActivityStatus::PendingReview
+            )
+            || (activity.visibility == ActivityVisibility::OrganizationOnly
+                && current_user.is_none());
+        if hidden {
+            return Err(ServiceError::NotFound);
+        }
+
+        Ok(ActivityDetail::public(activity))
+    }
+
+    async fn signup_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        body: SignupActivityBody,
+    ) -> ServiceResult<ActivitySignup> {
+        let mut tx = self.db.begin().await?;
+
+        // 1. 查询活动（加行锁，避免并发报名超过名额）
+        let activity = Self::fetch_activity(&mut tx, activity_id, true).await?;
+
+        if activity.deleted {
+            return Err(ServiceError::NotFound);
+        }
+
+        // 2. 活动状态、报名开关和报名时间
+        ensure_signup_allowed(&activity, chrono::Utc::now())?;
+
+        // 3. 按活动的报名表单校验答案（活动没有表单时不保存答案）
+        let form_answers = match &activity.form_schema {
+            Some(Json(fields)) => {
+                let answers = merge_builtin_answers(fields, body);
+                Some(form::validate_answers(fields, &answers).map_err(ServiceError::BadRequest)?)
+            }
+            None => None,
+        };
+
+        // 4. 名额检查：满员时进入候补队列，不占用名额
+        let waitlisted = matches!(activity.capacity, Some(cap) if activity.signup_count >= cap);
+
+        // 5. 查询用户是否已报名
+        let existing: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM activity_signups
+            WHERE user_id = ? AND activity_id = ?
+            "#,
+        )
+        .bind(&user.user_id)
+        .bind(activity_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if existing.is_some() {
+            return Err(ServiceError::Conflict("你已经报名过该活动".into()));
+        }
+
+        // 6. 插入报名记录（候补按自增 id 排队）
+        let status = if waitlisted {
+            ActivitySignupStatus::Waitlisted
+        } else {
+            ActivitySignupStatus::Applied
+        };
+        let signup_id = sqlx::query(
+            r#"
+            INSERT INTO activity_signups (user_id, activity_id, status, form_answers, created_at, updated_at)
+            VALUES (?, ?, ?, ?, NOW(), NOW())
+            "#,
+        )
+        .bind(&user.user_id)
+        .bind(activity_id)
+        .bind(status)
+        .bind(form_answers.map(Json))
+        .execute(&mut *tx)
+        .await?
+        .last_insert_id() as i64;
+
+        // 7. 更新活动报名人数（候补不计入）
+        if !waitlisted {
+            sqlx::query(
+                r#"
+                UPDATE activities
+                SET signup_count = signup_count + 1,
+                    updated_at = NOW()
+                WHERE id = ?
+                "#,
+            )
+            .bind(activity_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let signup = Self::fetch_signup(&mut tx, signup_id).await?;
+
+        tx.commit().await?;
+
+        Ok(signup)
+    }
+
+    async fn cancel_signup(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<ActivitySignup> {
+        let mut tx = self.db.begin().await?;
+
+        // 1. 锁住活动行，保证释放名额和候补递补在同一事务里完成
+        let activity = Self::fetch_activity(&mut tx, activity_id, true).await?;
+
+        // 2. 查询报名记录
+        let sql = format!(
+            "SELECT {} FROM activity_signups WHERE user_id = ? AND activity_id = ? FOR UPDATE",
+            SIGNUP_COLUMNS
+        );
+        let signup = sqlx::query_as::<_, ActivitySignup>(&sql)
+            .bind(&user.user_id)
+            .bind(activity_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(ServiceError::BadRequest("尚未报名该活动".into()))?;
+
+        // 3. 已取消则不允许再次取消
+        if signup.status == ActivitySignupStatus::Cancelled {
+            return Err(ServiceError::Conflict("你已经取消过报名".into()));
+        }
+
+        // 4. 更新报名状态
+        sqlx::query(
+            r#"
+            UPDATE activity_signups
+            SET status = 'CANCELLED',
+                updated_at = NOW()
+            WHERE id = ?
+            "#,
+        )
+        .bind(signup.id)
+        .execute(&mut *tx)
+        .await?;
+
+        // 5. 候补取消不影响名额；正式报名取消则释放名额并递补
+        if signup.status != ActivitySignupStatus::Waitlisted {
+            let mut signup_count = activity.signup_count - 1;
+
+            let has_seat = !matches!(activity.capacity, Some(cap) if signup_count >= cap);
+            if has_seat {
+                // 取最早的候补
+                let next: Option<i64> = sqlx::query_scalar(
+                    r#"
+                    SELECT id FROM activity_signups
+                    WHERE activity_id = ? AND status = 'WAITLISTED'
+                    ORDER BY id ASC
+                    LIMIT 1
+                    FOR UPDATE
+                    "#,
+                )
+                .bind(activity_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                if let Some(next_id) = next {
+                    sqlx::query(
+                        r#"
+                        UPDATE activity_signups
+                        SET status = 'APPLIED',
+                            updated_at = NOW()
+                        WHERE id = ?
+                        "#,
+                    )
+                    .bind(next_id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                    signup_count += 1;
+                }
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE activities
+                SET signup_count = ?,
+                    updated_at = NOW()
+                WHERE id = ?
+                "#,
+            )
+            .bind(signup_count)
+            .bind(activity_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let updated = Self::fetch_signup(&mut tx, signup.id).await?;
+
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    async fn list_my_activities(
+        &self,
+        user: &AuthUser,
+        query: ListMyActivitiesQuery,
+    ) -> ServiceResult<Paged<MyActivityItem>> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(10).clamp(1, 50);
+        let offset = (page - 1) * page_size;
+
+        // 1. 查总数（正式报名、已签到和候补都算“我的活动”）
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM activity_signups s
+            JOIN activities a ON s.activity_id = a.id
+            WHERE s.user_id = ? AND s.status <> 'CANCELLED'
+            "#,
+        )
+        .bind(&user.user_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        // 2. 查列表，候补的同时算出排在第几位
+        let list = sqlx::query_as::<_, MyActivityItem>(
+            r#"
+            SELECT
+                a.id, a.title, a.cover_url, a.summary,
+                a.category, a.tags, a.location,
+                a.start_time, a.end_time, a.signup_end_time,
+                a.capacity, a.signup_count, a.status,
+                a.visibility, a.organizer_name, a.is_official, a.signup_open,
+                s.status AS signup_status,
+                CASE WHEN s.status = 'WAITLISTED' THEN (
+                    SELECT COUNT(*)
+                    FROM activity_signups w
+                    WHERE w.activity_id = s.activity_id
+                      AND w.status = 'WAITLISTED'
+                      AND w.id <= s.id
+                ) END AS waitlist_position
+            FROM activity_signups s
+            JOIN activities a ON s.activity_id = a.id
+            WHERE s.user_id = ? AND s.status <> 'CANCELLED'
+            ORDER BY a.start_time DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(&user.user_id)
+        .bind(page_size)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(Paged { total, list })
+    }
+
+    async fn create_activity(
+        &self,
+        user: &AuthUser,
+        body: CreateActivityBody,
+    ) -> ServiceResult<Activity> {
+        if body.end_time <= body.start_time {
+            return Err(ServiceError::BadRequest("结束时间必须晚于开始时间".into()));
+        }
+        if let Some(fields) = &body.form_schema {
+            form::validate_schema(fields).map_err(ServiceError::BadRequest)?;
+        }
+
+        // 1. 处理默认值
+        let can_comment = body.can_comment.unwrap_or(true);
+        let signup_required = body.signup_required.unwrap_or(true);
+        let checkin_required = body.checkin_required.unwrap_or(false);
+
+        let status = ActivityStatus::Draft;
+        let visibility = body.visibility;
+
+        // 举办方名称取自用户表，举办方类型暂时写死
+        let organizer_name: String = sqlx::query_scalar("SELECT username FROM users WHERE id = ?")
+            .bind(&user.user_id)
+            .fetch_optional(&self.db)
+            .await?
+            .unwrap_or_default();
+        let organizer_type = "club".to_string();
+
+        let mut conn = self.db.acquire().await?;
+
+        // 2. 执行 INSERT，再按自增 id 回读成 Activity
+        let activity_id = sqlx::query(
+            r#"
+            INSERT INTO activities (
+                title, cover_url, summary, description, category, tags, location,
+                longitude, latitude, start_time, end_time, signup_start_time, signup_end_time,
+                capacity, signup_count, organizer_id, organizer_name, organizer_type,
+                status, visibility, can_comment, is_official, signup_required, checkin_required,
+                form_schema, created_at, updated_at, deleted
+            )
+            VALUES (
+                ?, ?, ?, ?, ?, ?, ?,
+                ?, ?, ?, ?, ?, ?,
+                ?, ?, ?, ?, ?,
+                ?, ?, ?, ?, ?, ?,
+                ?, NOW(), NOW(), FALSE
+            )
+            "#,
+        )
+        .bind(&body.title)
+        .bind(&body.cover_url)
+        .bind(&body.summary)
+        .bind(&body.description)
+        .bind(&body.category)
+        .bind(Json(&body.tags))           // Vec<String> -> JSON
+        .bind(&body.location)
+        .bind(None::<f64>)                // longitude 先空
+        .bind(None::<f64>)                // latitude 先空
+        .bind(body.start_time)
+        .bind(body.end_time)
+        .bind(body.signup_start_time)
+        .bind(body.signup_end_time)
+        .bind(body.capacity)
+        .bind(0_i32)                      // signup_count = 0
+        .bind(&user.user_id)
+        .bind(&organizer_name)
+        .bind(&organizer_type)
+        .bind(status)
+        .bind(visibility)
+        .bind(can_comment)
+        .bind(false)                      // is_official 默认 false
+        .bind(signup_required)
+        .bind(checkin_required)
+        .bind(body.form_schema.map(Json))
+        .execute(&mut *conn)
+        .await?
+        .last_insert_id() as i64;
+
+        Self::fetch_activity(&mut conn, activity_id, false).await
+    }
+
+    async fn update_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        body: UpdateActivityBody,
+    ) -> ServiceResult<Activity> {
+        let mut conn = self.db.acquire().await?;
+
+        // 1. 查活动
+        let activity = Self::fetch_activity(&mut conn, activity_id, false).await?;
+
+        // 2. 权限检查：只能编辑自己创建的活动
+        if activity.organizer_id != user.user_id {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        // 3. 状态检查：只允许草稿编辑（简单规则，后续可以放宽）
+        if activity.status != ActivityStatus::Draft {
+            return Err(ServiceError::BadRequest(
+                "只有草稿状态的活动可以编辑".into(),
+            ));
+        }
+
+        let start_time = body.start_time.unwrap_or(activity.start_time);
+        let end_time = body.end_time.unwrap_or(activity.end_time);
+        if end_time <= start_time {
+            return Err(ServiceError::BadRequest("结束时间必须晚于开始时间".into()));
+        }
+        if let Some(fields) = &body.form_schema {
+            form::validate_schema(fields).map_err(ServiceError::BadRequest)?;
+        }
+
+        // 4. 只更新传了的字段
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new("UPDATE activities SET updated_at = NOW()");
+
+        if let Some(title) = body.title {
+            qb.push(", title = ").push_bind(title);
+        }
+        if let Some(cover_url) = body.cover_url {
+            qb.push(", cover_url = ").push_bind(cover_url);
+        }
+        if let Some(summary) = body.summary {
+            qb.push(", summary = ").push_bind(summary);
+        }
+        if let Some(description) = body.description {
+            qb.push(", description = ").push_bind(description);
+        }
+        if let Some(category) = body.category {
+            qb.push(", category = ").push_bind(category);
+        }
+        if let Some(tags) = body.tags {
+            qb.push(", tags = ").push_bind(Json(tags));
+        }
+        if let Some(location) = body.location {
+            qb.push(", location = ").push_bind(location);
+        }
+        if let Some(start_time) = body.start_time {
+            qb.push(", start_time = ").push_bind(start_time);
+        }
+        if let Some(end_time) = body.end_time {
+            qb.push(", end_time = ").push_bind(end_time);
+        }
+        if let Some(signup_start_time) = body.signup_start_time {
+            qb.push(", signup_start_time = ").push_bind(signup_start_time);
+        }
+        if let Some(signup_end_time) = body.signup_end_time {
+            qb.push(", signup_end_time = ").push_bind(signup_end_time);
+        }
+        if let Some(capacity) = body.capacity {
+            qb.push(", capacity = ").push_bind(capacity);
+        }
+        if let Some(visibility) = body.visibility {
+            qb.push(", visibility = ").push_bind(visibility);
+        }
+        if let Some(signup_required) = body.signup_required {
+            qb.push(", signup_required = ").push_bind(signup_required);
+        }
+        if let Some(checkin_required) = body.checkin_required {
+            qb.push(", checkin_required = ").push_bind(checkin_required);
+        }
+        if let Some(can_comment) = body.can_comment {
+            qb.push(", can_comment = ").push_bind(can_comment);
+        }
+        if let Some(form_schema) = body.form_schema {
+            qb.push(", form_schema = ").push_bind(Json(form_schema));
+        }
+
+        qb.push(" WHERE id = ").push_bind(activity_id);
+        qb.build().execute(&mut *conn).await?;
+
+        Self::fetch_activity(&mut conn, activity_id, false).await
+    }
+
+    async fn submit_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<Activity> {
+        let mut tx = self.db.begin().await?;
+
+        // 1. 查活动
+        let activity = Self::fetch_activity(&mut tx, activity_id, true).await?;
+
+        if activity.organizer_id != user.user_id {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        // 2. DRAFT -> PENDING_REVIEW
+        let event =
+            Self::apply_transition(&mut tx, &activity, ActivityTransition::Submit, user, None).await?;
+
+        let updated = Self::fetch_activity(&mut tx, activity_id, false).await?;
+        tx.commit().await?;
+        self.events.publish(event);
+
+        Ok(updated)
+    }
+
+    async fn withdraw_activity(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<Activity> {
+        let mut tx = self.db.begin().await?;
+        let activity = Self::fetch_activity(&mut tx, activity_id, true).await?;
+
+        if activity.organizer_id != user.user_id {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        // PENDING_REVIEW -> DRAFT
+        let event =
+            Self::apply_transition(&mut tx, &activity, ActivityTransition::Withdraw, user, None).await?;
+
+        let updated = Self::fetch_activity(&mut tx, activity_id, false).await?;
+        tx.commit().await?;
+        self.events.publish(event);
+
+        Ok(updated)
+    }
+
+    async fn list_signups(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        page: i64,
+        page_size: i64,
+        status: Option<String>,
+    ) -> ServiceResult<Paged<ActivitySignup>> {
+        // 1. 权限：必须是该活动的举办方，或者管理员
+        let organizer_id: String = sqlx::query_scalar(
+            r#"
+            SELECT organizer_id
+            FROM activities
+            WHERE id = ? AND deleted = FALSE
+            "#,
+        )
+        .bind(activity_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(ServiceError::NotFound)?;
+
+        if organizer_id != user.user_id && !is_admin(user) {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        // 2. 分页参数
+        let page = page.max(1);
+        let mut size = page_size;
+        if size <= 0 {
+            size = 20;
+        } else if size > 100 {
+            size = 100;
+        }
+        let offset = (page - 1) * size;
+
+        // 3. 处理 status 过滤
+        let status_enum: Option<ActivitySignupStatus> = match status {
+            Some(s) => match s.as_str() {
+                "APPLIED" => Some(ActivitySignupStatus::Applied),
+                "CANCELLED" => Some(ActivitySignupStatus::Cancelled),
+                "CHECKED_IN" => Some(ActivitySignupStatus::CheckedIn),
+                "WAITLISTED" => Some(ActivitySignupStatus::Waitlisted),
+                _ => {
+                    return Err(ServiceError::BadRequest("无效的报名状态筛选".into()));
+                }
+            },
+            None => None,
+        };
+
+        // 4. 查询总数 & 列表
+        let mut count_qb: QueryBuilder<MySql> =
+            QueryBuilder::new("SELECT COUNT(*) FROM activity_signups WHERE activity_id = ");
+        count_qb.push_bind(activity_id);
+
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new(format!(
+            "SELECT {} FROM activity_signups WHERE activity_id = ",
+            SIGNUP_COLUMNS
+        ));
+        qb.push_bind(activity_id);
+
+        if let Some(st) = status_enum {
+            count_qb.push(" AND status = ").push_bind(st);
+            qb.push(" AND status = ").push_bind(st);
+        }
+
+        let total: i64 = count_qb.build_query_scalar().fetch_one(&self.db).await?;
+
+        qb.push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(size)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let list = qb
+            .build_query_as::<ActivitySignup>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(Paged { total, list })
+    }
+
+    async fn export_signups(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<(Vec<FormField>, SignupExportStream)> {
+        let mut conn = self.db.acquire().await?;
+        let activity = Self::fetch_activity(&mut conn, activity_id, false).await?;
+
+        // 与报名列表一致：已删除 / 下线的活动不再导出
+        if activity.deleted {
+            return Err(ServiceError::NotFound);
+        }
+
+        if activity.organizer_id != user.user_id && !is_admin(user) {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        // 逐行从数据库读取，不一次性加载全部报名
+        let pool = self.db.clone();
+        let rows = async_stream::try_stream! {
+            let mut rows = sqlx::query_as::<_, SignupExportRow>(
+                r#"
+                SELECT
+                    u.username, u.student_id, u.college,
+                    s.status, s.checkin_time, s.form_answers, s.created_at
+                FROM activity_signups s
+                LEFT JOIN users u ON u.id = s.user_id
+                WHERE s.activity_id = ?
+                ORDER BY s.id ASC
+                "#,
+            )
+            .bind(activity_id)
+            .fetch(&pool);
+
+            while let Some(row) = rows.try_next().await? {
+                yield row;
+            }
+        };
+
+        let fields = activity.form_schema.map(|Json(f)| f).unwrap_or_default();
+        Ok((fields, Box::pin(rows)))
+    }
+
+    async fn get_checkin_code(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+    ) -> ServiceResult<(String, chrono::DateTime<chrono::Utc>)> {
+        let mut conn = self.db.acquire().await?;
+        let activity = Self::fetch_activity(&mut conn, activity_id, false).await?;
+
+        if activity.deleted {
+            return Err(ServiceError::NotFound);
+        }
+
+        // 1. 只有举办方或管理员可以获取签到码
+        if activity.organizer_id != user.user_id && !is_admin(user) {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        // 2. 活动需要开启签到且已发布
+        if !activity.checkin_required {
+            return Err(ServiceError::BadRequest("该活动未开启签到".into()));
+        }
+        if activity.status != ActivityStatus::Published {
+            return Err(ServiceError::BadRequest("只有已发布的活动可以签到".into()));
+        }
+
+        // 3. 生成当前窗口的签到码，客户端在 expire_at 之后重新获取
+        Ok(self.checkin.issue(activity_id, chrono::Utc::now()))
+    }
+
+    async fn checkin(
+        &self,
+        user: &AuthUser,
+        activity_id: i64,
+        body: CheckinBody,
+    ) -> ServiceResult<ActivitySignup> {
+        // 1. 校验签到码
+        if !self
+            .checkin
+            .verify(activity_id, &body.checkin_token, chrono::Utc::now())
+        {
+            return Err(ServiceError::BadRequest("签到码无效或已过期".into()));
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        // 2. 校验活动
+        let activity = Self::fetch_activity(&mut tx, activity_id, false).await?;
+        if activity.deleted {
+            return Err(ServiceError::NotFound);
+        }
+        if !activity.checkin_required {
+            return Err(ServiceError::BadRequest("该活动未开启签到".into()));
+        }
+        if activity.status != ActivityStatus::Published {
+            return Err(ServiceError::BadRequest("只有已发布的活动可以签到".into()));
+        }
+
+        // 3. 查询报名记录（加行锁，防止重复签到）
+        let sql = format!(
+            "SELECT {} FROM activity_signups WHERE user_id = ? AND activity_id = ? FOR UPDATE",
+            SIGNUP_COLUMNS
+        );
+        let signup = sqlx::query_as::<_, ActivitySignup>(&sql)
+            .bind(&user.user_id)
+            .bind(activity_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(ServiceError::BadRequest("尚未报名该活动".into()))?;
+
+        match signup.status {
+            ActivitySignupStatus::Applied => {}
+            ActivitySignupStatus::CheckedIn => {
+                return Err(ServiceError::Conflict("你已经签到过了".into()));
+            }
+            ActivitySignupStatus::Cancelled => {
+                return Err(ServiceError::BadRequest("报名已取消，无法签到".into()));
+            }
+            ActivitySignupStatus::Waitlisted => {
+                return Err(ServiceError::BadRequest("仍在候补中，无法签到".into()));
+            }
+        }
+
+        // 4. 更新报名状态
+        sqlx::query(
+            r#"
+            UPDATE activity_signups
+            SET status = 'CHECKED_IN',
+                checkin_time = NOW(),
+                updated_at = NOW()
+            WHERE id = ?
+            "#,
+        )
+        .bind(signup.id)
+        .execute(&mut *tx)
+        .await?;
+
+        // 5. 记录签到流水
+        let method = body.method.unwrap_or_else(|| "QR_CODE".to_string());
+        sqlx::query(
+            r#"
+            INSERT INTO activity_checkins (activity_id, user_id, checkin_time, method, device_info)
+            VALUES (?, ?, NOW(), ?, ?)
+            "#,
+        )
+        .bind(activity_id)
+        .bind(&user.user_id)
+        .bind(&method)
+        .bind(&body.device_info)
+        .execute(&mut *tx)
+        .await?;
+
+        let updated = Self::fetch_signup(&mut tx, signup.id).await?;
+
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    async fn admin_review_activity(
+        &self,
+        admin: &AuthUser,
+        activity_id: i64,
+        body: ReviewActivityBody,
+    ) -> ServiceResult<Activity> {
+        // 1. 必须是管理员
+        if !is_admin(admin) {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        // 2. 根据 action 决定流转，驳回必须写明原因
+        let reason = body.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        let transition = match body.action.to_uppercase().as_str() {
+            "APPROVE" => ActivityTransition::Approve,
+            "REJECT" => {
+                if reason.is_none() {
+                    return Err(ServiceError::BadRequest("驳回时必须填写原因".into()));
+                }
+                ActivityTransition::Reject
+            }
+            _ => return Err(ServiceError::BadRequest("无效的审核动作".into())),
+        };
+
+        // 3. 查活动并流转（只有待审核的活动可以审核）
+        let mut tx = self.db.begin().await?;
+        let activity = Self::fetch_activity(&mut tx, activity_id, true).await?;
+
+        let event = Self::apply_transition(&mut tx, &activity, transition, admin, reason).await?;
+
+        let updated = Self::fetch_activity(&mut tx, activity_id, false).await?;
+        tx.commit().await?;
+        self.events.publish(event);
+
+        Ok(updated)
+    }
+
+    async fn admin_block_activity(
+        &self,
+        admin: &AuthUser,
+        activity_id: i64,
+        body: BlockActivityBody,
+    ) -> ServiceResult<Activity> {
+        // 1. 只有管理员可以强制下线
+        if !is_admin(admin) {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        let mut tx = self.db.begin().await?;
+        let activity = Self::fetch_activity(&mut tx, activity_id, true).await?;
+
+        // 2. 任意未下线状态 -> CANCELLED，并标记 deleted
+        let reason = body.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        let event =
+            Self::apply_transition(&mut tx, &activity, ActivityTransition::Block, admin, reason).await?;
+
+        let updated = Self::fetch_activity(&mut tx, activity_id, false).await?;
+        tx.commit().await?;
+        self.events.publish(event);
+
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_query() -> ListActivitiesQuery {
+        ListActivitiesQuery {
+            page: None,
+            page_size: None,
+            keyword: None,
+            category: None,
+            status: None,
+            only_joined: None,
+            start_time_from: None,
+            start_time_to: None,
+        }
+    }
+
+    fn user(id: &str) -> AuthUser {
+        AuthUser {
+            user_id: id.to_string(),
+            role: "student".to_string(),
+            jti: String::new(),
+            exp: 0,
+            family: String::new(),
+        }
+    }
+
+    fn activity(status: ActivityStatus) -> Activity {
+        let now = chrono::Utc::now();
+        Activity {
+            id: 1,
+            title: "讲座".into(),
+            cover_url: None,
+            summary: String::new(),
+            description: String::new(),
+            category: "lecture".into(),
+            tags: Json(vec![]),
+            location: "报告厅".into(),
+            longitude: None,
+            latitude: None,
+            start_time: now + chrono::Duration::days(7),
+            end_time: now + chrono::Duration::days(8),
+            signup_start_time: Some(now - chrono::Duration::days(1)),
+            signup_end_time: Some(now + chrono::Duration::days(1)),
+            capacity: Some(100),
+            signup_count: 0,
+            organizer_id: "org".into(),
+            organizer_name: "学生会".into(),
+            organizer_type: "club".into(),
+            status,
+            visibility: ActivityVisibility::Public,
+            can_comment: true,
+            is_official: false,
+            signup_required: true,
+            checkin_required: false,
+            signup_open: true,
+            form_schema: None,
+            created_at: now,
+            updated_at: now,
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn test_signup_requires_published_and_open() {
+        let now = chrono::Utc::now();
+        assert!(ensure_signup_allowed(&activity(ActivityStatus::Published), now).is_ok());
+
+        for status in [
+            ActivityStatus::Draft,
+            ActivityStatus::PendingReview,
+            ActivityStatus::Finished,
+            ActivityStatus::Cancelled,
+        ] {
+            assert!(
+                matches!(ensure_signup_allowed(&activity(status), now), Err(ServiceError::Conflict(_))),
+                "{:?}",
+                status
+            );
+        }
+
+        let closed = Activity { signup_open: false, ..activity(ActivityStatus::Published) };
+        assert!(matches!(ensure_signup_allowed(&closed, now), Err(ServiceError::Conflict(_))));
+
+        let not_started = Activity {
+            signup_start_time: Some(now + chrono::Duration::hours(1)),
+            ..activity(ActivityStatus::Published)
+        };
+        assert!(matches!(ensure_signup_allowed(&not_started, now), Err(ServiceError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_anonymous_list_only_sees_public_published() {
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new("SELECT COUNT(*) FROM activities a");
+        push_list_filters(&mut qb, None, &empty_query(), None);
+
+        let sql = qb.sql();
+        assert!(sql.contains("a.visibility = 'PUBLIC'"));
+        assert!(sql.contains("NOT IN ('DRAFT', 'PENDING_REVIEW')"));
+        assert!(!sql.contains("organizer_id"));
+    }
+
+    #[test]
+    fn test_list_filters_applied() {
+        let mut query = empty_query();
+        query.keyword = Some("  讲座 ".into());
+        query.category = Some("lecture".into());
+        query.only_joined = Some(true);
+        query.start_time_from = Some(chrono::Utc::now());
+
+        let u = user("u1");
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new("SELECT COUNT(*) FROM activities a");
+        push_list_filters(&mut qb, Some(&u), &query, Some(ActivityStatus::Published));
+
+        let sql = qb.sql();
+        assert!(sql.contains("OR a.organizer_id = ?"));
+        assert!(sql.contains("a.title LIKE ?"));
+        assert!(sql.contains("a.category = ?"));
+        assert!(sql.contains("a.status = ?"));
+        assert!(sql.contains("EXISTS (SELECT 1 FROM activity_signups s"));
+        assert!(sql.contains("a.start_time >= ?"));
+        assert!(!sql.contains("a.start_time <= ?"));
+    }
+}
@@ -0,0 +1,116 @@
+// src/modules/activity/state_machine.rs
+//
+// 活动状态机：所有状态变更都必须通过这里的转换表，非法的流转（如 FINISHED -> DRAFT）统一拒绝。
+//
+//   DRAFT ──submit──> PENDING_REVIEW ──approve──> PUBLISHED ──finish──> FINISHED
+//     ^                  │    │
+//     └──withdraw/reject─┘    │
+//   (除 CANCELLED 外任意状态) ──block──> CANCELLED
+
+use serde::Serialize;
+
+use crate::modules::activity::entity::{ActivityReviewAction, ActivityStatus};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivityTransition {
+    Submit,
+    Withdraw,
+    Approve,
+    Reject,
+    Block,
+    Finish,
+}
+
+impl ActivityTransition {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Submit => "提交审核",
+            Self::Withdraw => "撤回",
+            Self::Approve => "审核通过",
+            Self::Reject => "驳回",
+            Self::Block => "下线",
+            Self::Finish => "结束",
+        }
+    }
+
+    /// 需要写入审核历史的动作（自动结束由定时任务触发，不记录）
+    pub fn review_action(&self) -> Option<ActivityReviewAction> {
+        match self {
+            Self::Submit => Some(ActivityReviewAction::Submit),
+            Self::Withdraw => Some(ActivityReviewAction::Withdraw),
+            Self::Approve => Some(ActivityReviewAction::Approve),
+            Self::Reject => Some(ActivityReviewAction::Reject),
+            Self::Block => Some(ActivityReviewAction::Block),
+            Self::Finish => None,
+        }
+    }
+}
+
+fn status_label(status: ActivityStatus) -> &'static str {
+    match status {
+        ActivityStatus::Draft => "草稿",
+        ActivityStatus::PendingReview => "待审核",
+        ActivityStatus::Published => "已发布",
+        ActivityStatus::Finished => "已结束",
+        ActivityStatus::Cancelled => "已下线",
+    }
+}
+
+/// 转换表：返回目标状态，非法流转返回 None
+pub fn next_status(from: ActivityStatus, transition: ActivityTransition) -> Option<ActivityStatus> {
+    use ActivityStatus::*;
+    use ActivityTransition::*;
+
+    match (from, transition) {
+        (Draft, Submit) => Some(PendingReview),
+        (PendingReview, Withdraw) => Some(Draft),
+        (PendingReview, Approve) => Some(Published),
+        (PendingReview, Reject) => Some(Draft),
+        (Published, Finish) => Some(Finished),
+        (Draft | PendingReview | Published | Finished, Block) => Some(Cancelled),
+        _ => None,
+    }
+}
+
+/// 校验并返回目标状态，错误信息可以直接返回给客户端
+pub fn transition(from: ActivityStatus, transition: ActivityTransition) -> Result<ActivityStatus, String> {
+    next_status(from, transition).ok_or_else(|| {
+        format!(
+            "{}状态的活动不能{}",
+            status_label(from),
+            transition.label()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ActivityStatus::*;
+    use ActivityTransition::*;
+
+    #[test]
+    fn test_legal_transitions() {
+        assert_eq!(transition(Draft, Submit), Ok(PendingReview));
+        assert_eq!(transition(PendingReview, Withdraw), Ok(Draft));
+        assert_eq!(transition(PendingReview, Approve), Ok(Published));
+        assert_eq!(transition(PendingReview, Reject), Ok(Draft));
+        assert_eq!(transition(Published, Finish), Ok(Finished));
+        assert_eq!(transition(Published, Block), Ok(Cancelled));
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        assert!(transition(Finished, Submit).is_err());
+        assert!(transition(Finished, Withdraw).is_err());
+        assert!(transition(Draft, Approve).is_err());
+        assert!(transition(Published, Submit).is_err());
+        assert!(transition(Cancelled, Block).is_err());
+        assert!(transition(Draft, Finish).is_err());
+        assert_eq!(
+            transition(Finished, Reject).unwrap_err(),
+            "已结束状态的活动不能驳回"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+// src/modules/course/admin.rs
+//
+// 管理端维护学期和全校课程时用到的校验与 CSV 解析。
+//
+// 全校课程 CSV 需要带表头，列名与字段名一致：
+//   course_name,teacher_name,teacher_id,location,day_of_week,start_section,end_section,weeks_range,type,credits,description
+// 其中 weeks_range 写法与课表导入相同（`1-16`、`1-15单`、`1-8,10-16`），可选列可以省略或留空。
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use super::calendar::{self, SectionTimes};
+use super::entity::{FailedItem, PublicCourseInput};
+use super::import;
+
+/// 解析学期日期（YYYY-MM-DD）
+fn parse_date(s: &str, label: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| format!("{}格式应为 YYYY-MM-DD: {}", label, s))
+}
+
+/// 校验学期名称和起止日期
+pub fn validate_semester(name: &str, start_date: &str, end_date: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("学期名称不能为空".into());
+    }
+    let start = parse_date(start_date, "开始日期")?;
+    let end = parse_date(end_date, "结束日期")?;
+    if start >= end {
+        return Err("开始日期必须早于结束日期".into());
+    }
+    Ok(())
+}
+
+/// 学期共有多少个教学周（开始日期所在周为第 1 周）
+pub fn semester_weeks(start_date: &str, end_date: &str) -> Option<i32> {
+    let start = calendar::parse_semester_date(start_date)?;
+    let end = calendar::parse_semester_date(end_date)?;
+    let first_monday = calendar::class_date(start, 1, 1);
+    Some(((end - first_monday).num_days() / 7 + 1) as i32)
+}
+
+/// 校验一门全校课程：星期、节次（按学期作息表）和周次（见 validate_weeks）
+pub fn validate_course(
+    course: &PublicCourseInput,
+    sections: &SectionTimes,
+    max_week: Option<i32>,
+) -> Result<(), String> {
+    if course.course_name.trim().is_empty() {
+        return Err("课程名称不能为空".into());
+    }
+    if course.teacher_name.trim().is_empty() {
+        return Err("教师不能为空".into());
+    }
+    if !(1..=7).contains(&course.day_of_week) {
+        return Err("星期几必须在 1-7 之间".into());
+    }
+    sections.validate_span(course.start_section, course.end_section)?;
+    validate_weeks(&course.weeks_range, max_week)?;
+
+    if course.credits.is_some_and(|c| c < 0) {
+        return Err("学分不能为负数".into());
+    }
+    Ok(())
+}
+
+/// 校验周次：非空、不重复、从 1 开始，不超过学期周数（学期日期无效时不超过 MAX_WEEK）
+pub fn validate_weeks(weeks: &[i32], max_week: Option<i32>) -> Result<(), String> {
+    if weeks.is_empty() {
+        return Err("周次不能为空".into());
+    }
+    let mut sorted = weeks.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != weeks.len() {
+        return Err("周次重复".into());
+    }
+    if sorted[0] < 1 {
+        return Err("周次必须从 1 开始".into());
+    }
+    match max_week {
+        Some(max) if sorted[sorted.len() - 1] > max => {
+            return Err(format!("周次超出学期范围：本学期共 {} 周", max));
+        }
+        None if sorted[sorted.len() - 1] > import::MAX_WEEK => {
+            return Err(format!("周次不能超过第 {} 周", import::MAX_WEEK));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// CSV 中的一行
+#[derive(Debug, Deserialize)]
+struct CourseRecord {
+    course_name: String,
+    teacher_name: String,
+    #[serde(default)]
+    teacher_id: Option<i64>,
+    location: String,
+    day_of_week: i32,
+    start_section: i32,
+    end_section: i32,
+    weeks_range: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    credits: Option<i32>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// 解析全校课程 CSV；格式错误的行放进 FailedItem
+pub fn parse_courses_csv(bytes: &[u8]) -> Result<(Vec<PublicCourseInput>, Vec<FailedItem>), String> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(bytes);
+
+    reader.headers().map_err(|e| format!("CSV 表头解析失败: {}", e))?;
+
+    let mut courses = Vec::new();
+    let mut failed = Vec::new();
+
+    for (idx, record) in reader.deserialize::<CourseRecord>().enumerate() {
+        // 第 1 行是表头
+        let line = idx + 2;
+        let record = match record {
+            Ok(r) => r,
+            Err(e) => {
+                failed.push(FailedItem {
+                    course_name: String::new(),
+                    error_message: format!("第 {} 行: {}", line, e),
+                    conflict_with: None,
+                });
+                continue;
+            }
+        };
+
+        let Some(weeks_range) = import::parse_weeks(&record.weeks_range) else {
+            failed.push(FailedItem {
+                course_name: record.course_name,
+                error_message: format!("第 {} 行: 无法识别周次: {}", line, record.weeks_range),
+                conflict_with: None,
+            });
+            continue;
+        };
+
+        courses.push(PublicCourseInput {
+            id: None,
+            course_name: record.course_name,
+            teacher_name: record.teacher_name,
+            teacher_id: record.teacher_id,
+            location: record.location,
+            day_of_week: record.day_of_week,
+            start_section: record.start_section,
+            end_section: record.end_section,
+            weeks_range,
+            r#type: record.kind,
+            credits: record.credits,
+            description: record.description.filter(|d| !d.is_empty()),
+        });
+    }
+
+    Ok((courses, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn course() -> PublicCourseInput {
+        PublicCourseInput {
+            id: None,
+            course_name: "高等数学".into(),
+            teacher_name: "张老师".into(),
+            teacher_id: None,
+            location: "教一-101".into(),
+            day_of_week: 1,
+            start_section: 1,
+            end_section: 2,
+            weeks_range: vec![1, 2, 3],
+            r#type: "必修".into(),
+            credits: Some(4),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_semester() {
+        assert!(validate_semester("2024 春", "2024-02-26", "2024-07-05").is_ok());
+        assert!(validate_semester("", "2024-02-26", "2024-07-05").is_err());
+        assert!(validate_semester("2024 春", "2024-07-05", "2024-02-26").is_err());
+        assert!(validate_semester("2024 春", "2024/02/26", "2024-07-05").is_err());
+
+        // 2024-02-26 是周一，到 2024-06-16（第 16 周周日）正好 16 周
+        assert_eq!(semester_weeks("2024-02-26", "2024-06-16"), Some(16));
+        assert_eq!(semester_weeks("2024-02-26", "2024-06-17"), Some(17));
+    }
+
+    #[test]
+    fn test_validate_course() {
+        let sections = SectionTimes::default();
+        assert!(validate_course(&course(), &sections, Some(16)).is_ok());
+
+        for bad in [
+            PublicCourseInput { day_of_week: 0, ..course() },
+            PublicCourseInput { end_section: 13, ..course() },
+            PublicCourseInput { start_section: 3, ..course() },
+            PublicCourseInput { weeks_range: vec![], ..course() },
+            PublicCourseInput { weeks_range: vec![1, 1], ..course() },
+            PublicCourseInput { weeks_range: vec![0, 1], ..course() },
+            PublicCourseInput { weeks_range: vec![17], ..course() },
+        ] {
+            assert!(validate_course(&bad, &sections, Some(16)).is_err(), "{:?}", bad);
+        }
+
+        // 学期周数未知时按 MAX_WEEK 限制
+        let long = PublicCourseInput { weeks_range: (1..=import::MAX_WEEK + 1).collect(), ..course() };
+        assert!(validate_course(&long, &sections, None).is_err());
+    }
+
+    #[test]
+    fn test_validate_weeks() {
+        assert!(validate_weeks(&[3, 1, 2], Some(16)).is_ok());
+        assert!(validate_weeks(&[16], Some(16)).is_ok());
+        assert!(validate_weeks(&[30], None).is_ok());
+
+        for bad in [&[][..], &[1, 1], &[0, 1], &[-2], &[17], &[999]] {
+            assert!(validate_weeks(bad, Some(16)).is_err(), "{:?}", bad);
+        }
+        assert!(validate_weeks(&[31], None).is_err());
+    }
+
+    #[test]
+    fn test_parse_courses_csv() {
+        let csv = "\u{feff}course_name,teacher_name,location,day_of_week,start_section,end_section,weeks_range,type,credits\n\
+            高等数学,张老师,教一-101,1,1,2,1-4,必修,4\n\
+            线性代数,王老师,教二-201,二,3,4,1-4,必修,3\n\
+            大学物理,李老师,教三-301,3,5,6,1-7单,必修,\n\
+            概率论,赵老师,教一-102,4,1,2,1-2000000000,必修,2\n\
+            复变函数,钱老师,教一-103,5,1,2,16-1,必修,2\n";
+
+        let (courses, failed) = parse_courses_csv(csv.as_bytes()).unwrap();
+        assert_eq!(courses.len(), 2);
+        assert_eq!(courses[0].weeks_range, vec![1, 2, 3, 4]);
+        assert_eq!(courses[1].weeks_range, vec![1, 3, 5, 7]);
+        assert_eq!(courses[1].credits, None);
+        assert_eq!(failed.len(), 3);
+        assert!(failed[0].error_message.starts_with("第 3 行"));
+        // 超大区间和起止颠倒的周次都按无法识别处理
+        assert!(failed[1].error_message.starts_with("第 5 行: 无法识别周次"));
+        assert!(failed[2].error_message.starts_with("第 6 行: 无法识别周次"));
+    }
+}
@@ -0,0 +1,447 @@
+// src/modules/course/calendar.rs
+//
+// 课表导出为 iCalendar（.ics）：
+// - 节次 -> 上下课时间由 SectionTimes 提供：优先使用学期在 section_times 表里配置的作息表，
+//   没有配置时使用默认作息表（可用 COURSE_SECTION_TIMES 覆盖）
+// - 每个课表项按周次拆成若干段等间隔的周次（如 1-16 周、单周、双周），
+//   每段生成一个带 RRULE 的 VEVENT，第一次上课日期由学期开始日期推算
+// - 时间统一使用 Asia/Shanghai 本地时间（无夏令时）
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+
+use super::entity::{ScheduleItem, Semester};
+
+/// 日历时区
+const TZID: &str = "Asia/Shanghai";
+
+/// 默认作息表：第 1 节开始依次排列
+const DEFAULT_SECTION_TIMES: &str = "08:00-08:45,08:55-09:40,10:00-10:45,10:55-11:40,\
+14:00-14:45,14:55-15:40,16:00-16:45,16:55-17:40,19:00-19:45,19:55-20:40,20:50-21:35,21:45-22:30";
+
+/// 节次作息表，下标 0 对应第 1 节
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionTimes {
+    sections: Vec<(NaiveTime, NaiveTime)>,
+}
+
+impl SectionTimes {
+    pub fn new(sections: Vec<(NaiveTime, NaiveTime)>) -> Self {
+        Self { sections }
+    }
+
+    /// 解析 `08:00-08:45,08:55-09:40,...` 格式的作息表
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut sections = Vec::new();
+
+        for (idx, part) in s.split(',').map(str::trim).filter(|p| !p.is_empty()).enumerate() {
+            let (start, end) = part
+                .split_once('-')
+                .ok_or_else(|| format!("第 {} 节时间格式错误: {}", idx + 1, part))?;
+            let start = parse_time(start).ok_or_else(|| format!("第 {} 节开始时间无效: {}", idx + 1, start))?;
+            let end = parse_time(end).ok_or_else(|| format!("第 {} 节结束时间无效: {}", idx + 1, end))?;
+            if start >= end {
+                return Err(format!("第 {} 节开始时间必须早于结束时间", idx + 1));
+            }
+            if let Some(&(_, prev_end)) = sections.last() {
+                if start < prev_end {
+                    return Err(format!("第 {} 节与上一节时间重叠", idx + 1));
+                }
+            }
+            sections.push((start, end));
+        }
+
+        if sections.is_empty() {
+            return Err("作息表不能为空".into());
+        }
+        Ok(Self { sections })
+    }
+
+    /// 由 section_times 表的行构造，节次必须从 1 开始连续
+    pub fn from_rows(mut rows: Vec<(i32, NaiveTime, NaiveTime)>) -> Result<Self, String> {
+        rows.sort_by_key(|(section, _, _)| *section);
+
+        let mut sections = Vec::with_capacity(rows.len());
+        for (idx, (section, start, end)) in rows.into_iter().enumerate() {
+            if section != idx as i32 + 1 {
+                return Err(format!("作息表缺少第 {} 节", idx + 1));
+            }
+            if start >= end {
+                return Err(format!("第 {} 节开始时间必须早于结束时间", section));
+            }
+            if let Some(&(_, prev_end)) = sections.last() {
+                if start < prev_end {
+                    return Err(format!("第 {} 节与上一节时间重叠", section));
+                }
+            }
+            sections.push((start, end));
+        }
+
+        if sections.is_empty() {
+            return Err("作息表不能为空".into());
+        }
+        Ok(Self { sections })
+    }
+
+    /// 读取 COURSE_SECTION_TIMES，未配置时使用默认作息表
+    pub fn from_env() -> Self {
+        match std::env::var("COURSE_SECTION_TIMES") {
+            Ok(v) => Self::parse(&v).expect("COURSE_SECTION_TIMES is invalid"),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 第 section 节的上下课时间（从 1 开始）
+    pub fn get(&self, section: i32) -> Option<(NaiveTime, NaiveTime)> {
+        usize::try_from(section)
+            .ok()
+            .and_then(|s| s.checked_sub(1))
+            .and_then(|idx| self.sections.get(idx))
+            .copied()
+    }
+
+    /// 第 start 节上课到第 end 节下课
+    pub fn span(&self, start: i32, end: i32) -> Option<(NaiveTime, NaiveTime)> {
+        if start > end {
+            return None;
+        }
+        Some((self.get(start)?.0, self.get(end)?.1))
+    }
+
+    /// 校验节次范围存在于作息表中
+    pub fn validate_span(&self, start: i32, end: i32) -> Result<(), String> {
+        if start > end {
+            return Err("开始节次不能大于结束节次".into());
+        }
+        if start < 1 || end as usize > self.sections.len() {
+            return Err(format!("节次不存在：本学期共 {} 节", self.sections.len()));
+        }
+        Ok(())
+    }
+
+    /// 按节次顺序返回 (节次, 上课时间, 下课时间)
+    pub fn iter(&self) -> impl Iterator<Item = (i32, NaiveTime, NaiveTime)> + '_ {
+        self.sections
+            .iter()
+            .enumerate()
+            .map(|(idx, &(start, end))| (idx as i32 + 1, start, end))
+    }
+
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
+
+impl Default for SectionTimes {
+    fn default() -> Self {
+        Self::parse(DEFAULT_SECTION_TIMES).expect("default section times are valid")
+    }
+}
+
+/// 接口里统一使用 `HH:MM` 表示时间
+pub fn format_time(t: NaiveTime) -> String {
+    t.format("%H:%M").to_string()
+}
+
+/// 给课表项填上解析后的上下课时间；节次不在作息表内时保持为空
+pub fn resolve_times(items: &mut [ScheduleItem], sections: &SectionTimes) {
+    for item in items {
+        let span = sections.span(item.start_section, item.end_section);
+        item.start_time = span.map(|(start, _)| format_time(start));
+        item.end_time = span.map(|(_, end)| format_time(end));
+    }
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    let s = s.trim();
+    NaiveTime::parse_from_str(s, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+        .ok()
+}
+
+/// 学期开始日期（兼容 `2024-02-26` 和 `2024-02-26 00:00:00`）
+pub fn parse_semester_date(s: &str) -> Option<NaiveDate> {
+    s.get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+/// 一段等间隔的周次：从 start 周开始，每 step 周上一次，共 count 次
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekRun {
+    pub start: i32,
+    pub step: i32,
+    pub count: i32,
+}
+
+/// 把周次列表拆成尽量少的等间隔段，如 [1,2,3,5,7,9] -> 1-3 周 + 5-9 单周
+pub fn week_runs(weeks: &[i32]) -> Vec<WeekRun> {
+    let mut weeks: Vec<i32> = weeks.iter().copied().filter(|w| *w > 0).collect();
+    weeks.sort_unstable();
+    weeks.dedup();
+
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < weeks.len() {
+        let Some(&next) = weeks.get(i + 1) else {
+            runs.push(WeekRun { start: weeks[i], step: 1, count: 1 });
+            break;
+        };
+
+        let step = next - weeks[i];
+        let mut j = i + 1;
+        while j + 1 < weeks.len() && weeks[j + 1] - weeks[j] == step {
+            j += 1;
+        }
+
+        runs.push(WeekRun { start: weeks[i], step, count: (j - i + 1) as i32 });
+        i = j + 1;
+    }
+
+    runs
+}
+
+/// 第 week 周星期 day_of_week 的日期；学期开始日期所在的那一周为第 1 周
+pub fn class_date(semester_start: NaiveDate, week: i32, day_of_week: i32) -> NaiveDate {
+    let monday = semester_start - Duration::days(semester_start.weekday().num_days_from_monday() as i64);
+    monday + Duration::days(((week - 1) * 7 + (day_of_week - 1)) as i64)
+}
+
+/// 生成整个学期课表的 iCalendar 文本
+pub fn build_calendar(
+    semester: &Semester,
+    items: &[ScheduleItem],
+    sections: &SectionTimes,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    let start_date = parse_semester_date(&semester.start_date)
+        .ok_or_else(|| format!("学期开始日期格式错误: {}", semester.start_date))?;
+    let dtstamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//campus_backend//schedule//CN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(&format!("{} 课表", semester.name))),
+        format!("X-WR-TIMEZONE:{}", TZID),
+        "BEGIN:VTIMEZONE".to_string(),
+        format!("TZID:{}", TZID),
+        "BEGIN:STANDARD".to_string(),
+        "DTSTART:19700101T000000".to_string(),
+        "TZOFFSETFROM:+0800".to_string(),
+        "TZOFFSETTO:+0800".to_string(),
+        "TZNAME:CST".to_string(),
+        "END:STANDARD".to_string(),
+        "END:VTIMEZONE".to_string(),
+    ];
+
+    for item in items {
+        let Some((begin, end)) = sections.span(item.start_section, item.end_section) else {
+            tracing::warn!(
+                item_id = item.id,
+                start_section = item.start_section,
+                end_section = item.end_section,
+                "schedule item has sections outside the timetable, skipped in ics"
+            );
+            continue;
+        };
+        if !(1..=7).contains(&item.day_of_week) {
+            continue;
+        }
+
+        for run in week_runs(&item.weeks_range) {
+            let date = class_date(start_date, run.start, item.day_of_week);
+
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:schedule-{}-w{}@campus_backend", item.id, run.start));
+            lines.push(format!("DTSTAMP:{}", dtstamp));
+            lines.push(format!("DTSTART;TZID={}:{}", TZID, date.and_time(begin).format("%Y%m%dT%H%M%S")));
+            lines.push(format!("DTEND;TZID={}:{}", TZID, date.and_time(end).format("%Y%m%dT%H%M%S")));
+            if run.count > 1 {
+                lines.push(format!("RRULE:FREQ=WEEKLY;INTERVAL={};COUNT={}", run.step, run.count));
+            }
+            lines.push(format!("SUMMARY:{}", escape_text(&item.course_name)));
+            if let Some(location) = item.location.as_deref().filter(|l| !l.is_empty()) {
+                lines.push(format!("LOCATION:{}", escape_text(location)));
+            }
+            lines.push(format!("DESCRIPTION:{}", escape_text(&describe(item))));
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in &lines {
+        fold_line(line, &mut out);
+    }
+    Ok(out)
+}
+
+fn describe(item: &ScheduleItem) -> String {
+    let mut parts = Vec::new();
+    if let Some(teacher) = item.teacher_name.as_deref().filter(|t| !t.is_empty()) {
+        parts.push(format!("教师：{}", teacher));
+    }
+    parts.push(format!("第 {}-{} 节", item.start_section, item.end_section));
+    if let Some(kind) = item.r#type.as_deref().filter(|t| !t.is_empty()) {
+        parts.push(format!("类型：{}", kind));
+    }
+    if let Some(desc) = item.description.as_deref().filter(|d| !d.is_empty()) {
+        parts.push(desc.to_string());
+    }
+    parts.join("\n")
+}
+
+/// RFC 5545 3.3.11：转义反斜杠、分号、逗号和换行
+fn escape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// RFC 5545 3.1：每行不超过 75 字节，续行以空格开头，不能截断 UTF-8 字符
+fn fold_line(line: &str, out: &mut String) {
+    const LIMIT: usize = 75;
+    let mut width = 0;
+
+    for c in line.chars() {
+        if width + c.len_utf8() > LIMIT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(weeks: Vec<i32>) -> ScheduleItem {
+        ScheduleItem {
+            id: 7,
+            source_id: None,
+            course_name: "高等数学, 上".into(),
+            teacher_name: Some("张老师".into()),
+            location: Some("教一-101".into()),
+            day_of_week: 3,
+            start_section: 3,
+            end_section: 4,
+            weeks_range: weeks,
+            r#type: None,
+            credits: None,
+            description: None,
+            color_hex: "#FFFFFF".into(),
+            is_custom: true,
+            start_time: None,
+            end_time: None,
+        }
+    }
+
+    #[test]
+    fn test_week_runs() {
+        assert_eq!(
+            week_runs(&[3, 1, 2, 5, 7, 9, 9]),
+            vec![
+                WeekRun { start: 1, step: 1, count: 3 },
+                WeekRun { start: 5, step: 2, count: 3 },
+            ]
+        );
+        assert_eq!(week_runs(&[4]), vec![WeekRun { start: 4, step: 1, count: 1 }]);
+        assert!(week_runs(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_section_times() {
+        let times = SectionTimes::default();
+        assert_eq!(times.len(), 12);
+        assert_eq!(
+            times.span(1, 2),
+            Some((parse_time("08:00").unwrap(), parse_time("09:40").unwrap()))
+        );
+        assert_eq!(times.get(0), None);
+        assert_eq!(times.get(13), None);
+        assert!(SectionTimes::parse("08:00-08:45,08:30-09:00").is_err());
+        assert!(SectionTimes::parse("09:00-08:00").is_err());
+
+        assert!(times.validate_span(1, 12).is_ok());
+        assert!(times.validate_span(11, 99).is_err());
+        assert!(times.validate_span(0, 2).is_err());
+        assert!(times.validate_span(3, 2).is_err());
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let t = |s| parse_time(s).unwrap();
+        let summer = SectionTimes::from_rows(vec![
+            (2, t("09:00"), t("09:45")),
+            (1, t("08:00"), t("08:45")),
+        ])
+        .unwrap();
+        assert_eq!(summer.len(), 2);
+        assert_eq!(summer.span(1, 2), Some((t("08:00"), t("09:45"))));
+
+        assert!(SectionTimes::from_rows(vec![(2, t("09:00"), t("09:45"))]).is_err());
+        assert!(SectionTimes::from_rows(vec![]).is_err());
+
+        let mut items = vec![item(vec![1])];
+        resolve_times(&mut items, &SectionTimes::default());
+        assert_eq!(items[0].start_time.as_deref(), Some("10:00"));
+        assert_eq!(items[0].end_time.as_deref(), Some("11:40"));
+    }
+
+    #[test]
+    fn test_build_calendar() {
+        let semester = Semester {
+            id: 1,
+            name: "2024 春".into(),
+            // 周三开学，第 1 周仍从当周周一算起
+            start_date: "2024-02-28".into(),
+            end_date: "2024-07-01".into(),
+            is_current: true,
+        };
+        let now = DateTime::from_timestamp(0, 0).unwrap();
+
+        let ics = build_calendar(&semester, &[item(vec![1, 2, 3, 4])], &SectionTimes::default(), now).unwrap();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART;TZID=Asia/Shanghai:20240228T100000\r\n"));
+        assert!(ics.contains("DTEND;TZID=Asia/Shanghai:20240228T114000\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;INTERVAL=1;COUNT=4\r\n"));
+        assert!(ics.contains("SUMMARY:高等数学\\, 上\r\n"));
+        assert!(ics.contains("LOCATION:教一-101\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+
+        // 超出作息表的节次不导出
+        let mut bad = item(vec![1]);
+        bad.end_section = 99;
+        let ics = build_calendar(&semester, &[bad], &SectionTimes::default(), now).unwrap();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 0);
+    }
+
+    #[test]
+    fn test_fold_line_keeps_utf8() {
+        let mut out = String::new();
+        fold_line(&format!("SUMMARY:{}", "课".repeat(40)), &mut out);
+        for line in out.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        assert_eq!(out.replace("\r\n ", ""), format!("SUMMARY:{}\r\n", "课".repeat(40)));
+    }
+}
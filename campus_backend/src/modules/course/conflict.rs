@@ -0,0 +1,101 @@
+// src/modules/course/conflict.rs
+//
+// 课表时间冲突检测：同一天、节次区间相交、且至少有一周重叠即视为冲突。
+// 批量添加时先把用户本学期已有的课表项装进 ConflictIndex，
+// 每接受一条新课表项就放进去，保证同一批次内的课表项之间也会互相检查。
+
+use std::collections::{BTreeSet, HashMap};
+
+/// 参与冲突检测的课表项
+#[derive(Debug, Clone)]
+pub struct Slot {
+    pub course_name: String,
+    pub day_of_week: i32,
+    pub start_section: i32,
+    pub end_section: i32,
+    pub weeks: BTreeSet<i32>,
+}
+
+impl Slot {
+    pub fn new(
+        course_name: impl Into<String>,
+        day_of_week: i32,
+        start_section: i32,
+        end_section: i32,
+        weeks: &[i32],
+    ) -> Self {
+        Self {
+            course_name: course_name.into(),
+            day_of_week,
+            start_section,
+            end_section,
+            weeks: weeks.iter().copied().collect(),
+        }
+    }
+
+    pub fn overlaps(&self, other: &Slot) -> bool {
+        self.day_of_week == other.day_of_week
+            && self.start_section <= other.end_section
+            && other.start_section <= self.end_section
+            && !self.weeks.is_disjoint(&other.weeks)
+    }
+}
+
+/// 按星期分桶的课表项集合
+#[derive(Debug, Default)]
+pub struct ConflictIndex {
+    by_day: HashMap<i32, Vec<Slot>>,
+}
+
+impl ConflictIndex {
+    pub fn new(slots: impl IntoIterator<Item = Slot>) -> Self {
+        let mut index = Self::default();
+        for slot in slots {
+            index.insert(slot);
+        }
+        index
+    }
+
+    pub fn insert(&mut self, slot: Slot) {
+        self.by_day.entry(slot.day_of_week).or_default().push(slot);
+    }
+
+    /// 返回第一个与之冲突的课表项
+    pub fn find_conflict(&self, slot: &Slot) -> Option<&Slot> {
+        self.by_day
+            .get(&slot.day_of_week)?
+            .iter()
+            .find(|existing| existing.overlaps(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlap_rules() {
+        let base = Slot::new("高数", 1, 3, 4, &[1, 2, 3]);
+
+        assert!(base.overlaps(&Slot::new("A", 1, 4, 5, &[3, 4])));
+        // 不同天 / 节次不相交 / 周次不相交
+        assert!(!base.overlaps(&Slot::new("B", 2, 3, 4, &[1])));
+        assert!(!base.overlaps(&Slot::new("C", 1, 5, 6, &[1])));
+        assert!(!base.overlaps(&Slot::new("D", 1, 3, 4, &[4, 5])));
+    }
+
+    #[test]
+    fn test_batch_items_checked_against_each_other() {
+        let mut index = ConflictIndex::new([Slot::new("高数", 1, 1, 2, &[1, 2])]);
+
+        let first = Slot::new("自习", 3, 1, 2, &[1]);
+        assert!(index.find_conflict(&first).is_none());
+        index.insert(first);
+
+        let second = Slot::new("社团", 3, 2, 3, &[1]);
+        assert_eq!(index.find_conflict(&second).unwrap().course_name, "自习");
+
+        let third = Slot::new("英语", 1, 2, 2, &[2]);
+        assert_eq!(index.find_conflict(&third).unwrap().course_name, "高数");
+    }
+}
@@ -1,36 +1,86 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::{header, StatusCode},
-    response::IntoResponse,
+    middleware,
+    response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
     Router,
 };
-use prost::Message;
-use sqlx::MySqlPool;
+use serde::Deserialize;
 
-use crate::common::{auth::AuthUser, AppError};
+use crate::common::{
+    auth::{Admin, AuthUser, RequireRole},
+    error::AppError,
+    negotiate::{self, AcceptFormat, Format, ProtoOrJson},
+    response::OK_CODE,
+};
 use crate::common::state::AppState;
 
-use super::{entity, service};
+use super::{admin, calendar, entity, import, service};
 
 // Protobuf
-mod proto;
-use proto::*;
+use super::proto::*;
 
 /// 注册课程模块路由
 pub fn router() -> Router<AppState> {
+    // 返回 text/calendar 的接口不参与 protobuf / JSON 协商，错误沿用全局的 JSON 信封
+    let calendar = Router::new()
+        .route("/api/v1/schedule/ics", get(export_schedule_ics_handler))
+        .route("/api/v1/schedule/feed/:token", get(schedule_feed_handler));
+
     Router::new()
         .route("/api/v1/semesters", get(get_semesters_handler))
+        .route("/api/v1/semesters/:id/section-times", get(get_section_times_handler))
         .route("/api/v1/courses", get(get_public_courses_handler))
         .route("/api/v1/schedule", get(get_schedule_handler))
         .route("/api/v1/schedule", post(add_schedule_items_handler))
         .route("/api/v1/schedule", patch(update_schedule_item_handler))
         .route("/api/v1/schedule", delete(delete_schedule_item_handler))
+        .route("/api/v1/schedule/import", post(import_schedule_handler))
+        .route("/api/v1/schedule/ics/subscription", get(get_schedule_subscription_handler))
+        .route("/api/v1/schedule/ics/subscription/rotate", post(rotate_schedule_subscription_handler))
+        // 管理端：学期与全校课程维护
+        .route("/api/v1/admin/semesters", post(create_semester_handler))
+        .route(
+            "/api/v1/admin/semesters/:id",
+            patch(update_semester_handler).delete(delete_semester_handler),
+        )
+        .route("/api/v1/admin/semesters/:id/current", post(set_current_semester_handler))
+        .route("/api/v1/admin/public-courses/import", post(upsert_public_courses_handler))
+        // 错误响应与成功响应使用同一编码（protobuf / JSON）
+        .layer(middleware::from_fn(negotiate::negotiate_errors))
+        .merge(calendar)
+}
+
+/// 导入课表的查询参数
+#[derive(Debug, Deserialize)]
+struct ImportScheduleQuery {
+    semester_id: i64,
+    /// csv / xls / xlsx / html，不传时按文件内容判断
+    format: Option<String>,
+    /// 只预览解析和冲突检测结果，不写入
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// 全校课程导入的查询参数
+#[derive(Debug, Deserialize)]
+struct UpsertCoursesQuery {
+    semester_id: i64,
+    /// csv / protobuf，不传时按 Content-Type 判断
+    format: Option<String>,
+}
+
+/// 导出 / 订阅课表的查询参数
+#[derive(Debug, Deserialize)]
+struct CalendarQuery {
+    semester_id: i64,
 }
 
 /// 获取学期列表
 async fn get_semesters_handler(
     State(state): State<AppState>,
+    format: AcceptFormat,
 ) -> Result<impl IntoResponse, AppError> {
     let semesters = service::get_semesters(&state.pool).await?;
 
@@ -46,21 +96,52 @@ async fn get_semesters_handler(
         .collect();
 
     let response = GetSemestersResponse {
-        code: 200,
+        code: OK_CODE,
         message: "成功".to_string(),
         data: Some(GetSemestersData { semesters: proto_semesters }),
     };
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "application/x-protobuf")],
-        response.encode_to_vec(),
-    ))
+    Ok(format.reply(&response))
+}
+
+/// 获取学期作息表（未单独配置的学期返回默认作息表）
+async fn get_section_times_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    Path(semester_id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    service::get_semester(&state.pool, semester_id).await?;
+
+    let configured = service::get_section_times(&state.pool, semester_id).await?;
+    let is_default = configured.is_none();
+    let sections = configured.unwrap_or_else(|| state.section_times.as_ref().clone());
+
+    let section_times: Vec<SectionTime> = sections
+        .iter()
+        .map(|(section, start, end)| SectionTime {
+            section,
+            start_time: calendar::format_time(start),
+            end_time: calendar::format_time(end),
+        })
+        .collect();
+
+    let response = GetSectionTimesResponse {
+        code: OK_CODE,
+        message: "成功".to_string(),
+        data: Some(GetSectionTimesData {
+            semester_id,
+            is_default,
+            section_times,
+        }),
+    };
+
+    Ok(format.reply(&response))
 }
 
 /// 获取全校课程
 async fn get_public_courses_handler(
     State(state): State<AppState>,
+    format: AcceptFormat,
     Query(query): Query<GetCoursesQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let params = entity::GetCoursesParams {
@@ -92,7 +173,7 @@ async fn get_public_courses_handler(
         .collect();
 
     let response = GetPublicCoursesResponse {
-        code: 200,
+        code: OK_CODE,
         message: "成功".to_string(),
         data: Some(GetPublicCoursesData {
             list: proto_courses,
@@ -105,64 +186,75 @@ async fn get_public_courses_handler(
         }),
     };
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "application/x-protobuf")],
-        response.encode_to_vec(),
-    ))
+    Ok(format.reply(&response))
+}
+
+fn schedule_item_to_proto(item: entity::ScheduleItem) -> ScheduleItem {
+    ScheduleItem {
+        id: item.id,
+        source_id: item.source_id,
+        course_name: item.course_name,
+        teacher_name: item.teacher_name,
+        location: item.location,
+        day_of_week: item.day_of_week,
+        start_section: item.start_section,
+        end_section: item.end_section,
+        weeks: item.weeks_range,
+        r#type: item.r#type,
+        credits: item.credits,
+        description: item.description,
+        color_hex: item.color_hex,
+        is_custom: item.is_custom,
+        start_time: item.start_time,
+        end_time: item.end_time,
+    }
+}
+
+fn failed_item_to_proto(item: entity::FailedItem) -> FailedItem {
+    FailedItem {
+        course_name: item.course_name,
+        error_message: item.error_message,
+        conflict_with: item.conflict_with,
+    }
 }
 
 /// 获取用户课表
 async fn get_schedule_handler(
     State(state): State<AppState>,
+    format: AcceptFormat,
     Query(query): Query<GetScheduleQuery>,
     auth_user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = auth_user.user_id;
-
-    let items = service::get_user_schedule(&state.pool, user_id, query.semester_id, query.week).await?;
+    let items = service::get_user_schedule(
+        &state.pool,
+        &auth_user.user_id,
+        query.semester_id,
+        query.week,
+        &state.section_times,
+    )
+        .await?;
 
     let proto_items: Vec<ScheduleItem> = items
         .into_iter()
-        .map(|item| ScheduleItem {
-            id: item.id,
-            source_id: item.source_id,
-            course_name: item.course_name,
-            teacher_name: item.teacher_name,
-            location: item.location,
-            day_of_week: item.day_of_week,
-            start_section: item.start_section,
-            end_section: item.end_section,
-            weeks: item.weeks_range,
-            r#type: item.r#type,
-            credits: item.credits,
-            description: item.description,
-            color_hex: item.color_hex,
-            is_custom: item.is_custom,
-        })
+        .map(schedule_item_to_proto)
         .collect();
 
     let response = GetScheduleResponse {
-        code: 200,
+        code: OK_CODE,
         message: "成功".to_string(),
         data: Some(GetScheduleData { items: proto_items }),
     };
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "application/x-protobuf")],
-        response.encode_to_vec(),
-    ))
+    Ok(format.reply(&response))
 }
 
 /// 批量增加课表项
 async fn add_schedule_items_handler(
     State(state): State<AppState>,
+    format: AcceptFormat,
     auth_user: AuthUser,
-    body: axum::body::Bytes,
+    ProtoOrJson(proto_req): ProtoOrJson<AddScheduleItemsRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let proto_req = AddScheduleItemsRequest::decode(body)?;
-
     let items: Vec<entity::ScheduleItemInput> = proto_req
         .items
         .into_iter()
@@ -183,38 +275,27 @@ async fn add_schedule_items_handler(
         })
         .collect();
 
-    let result = service::add_schedule_items(&state.pool, auth_user.user_id, proto_req.semester_id, items).await?;
+    let result = service::add_schedule_items(
+        &state.pool,
+        &auth_user.user_id,
+        proto_req.semester_id,
+        items,
+        &state.section_times,
+    )
+        .await?;
 
     let successful_items: Vec<ScheduleItem> = result.successful_items
         .into_iter()
-        .map(|item| ScheduleItem {
-            id: item.id,
-            source_id: item.source_id,
-            course_name: item.course_name,
-            teacher_name: item.teacher_name,
-            location: item.location,
-            day_of_week: item.day_of_week,
-            start_section: item.start_section,
-            end_section: item.end_section,
-            weeks: item.weeks_range,
-            r#type: item.r#type,
-            credits: item.credits,
-            description: item.description,
-            color_hex: item.color_hex,
-            is_custom: item.is_custom,
-        })
+        .map(schedule_item_to_proto)
         .collect();
 
     let failed_items: Vec<FailedItem> = result.failed_items
         .into_iter()
-        .map(|item| FailedItem {
-            course_name: item.course_name,
-            error_message: item.error_message,
-        })
+        .map(failed_item_to_proto)
         .collect();
 
     let response = AddScheduleItemsResponse {
-        code: 200,
+        code: OK_CODE,
         message: "处理完成".into(),
         data: Some(AddScheduleItemsData {
             successful_items,
@@ -222,22 +303,71 @@ async fn add_schedule_items_handler(
         }),
     };
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "application/x-protobuf")],
-        response.encode_to_vec(),
-    ))
+    Ok(format.reply(&response))
+}
+
+/// 从教务系统导出的文件导入课表（请求体为文件原始内容）
+async fn import_schedule_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    Query(query): Query<ImportScheduleQuery>,
+    auth_user: AuthUser,
+    body: axum::body::Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    if body.is_empty() {
+        return Err(AppError::BadRequest("导入文件不能为空".to_string()));
+    }
+
+    let file_format = match query.format.as_deref() {
+        Some(f) => import::ImportFormat::parse(f)
+            .ok_or_else(|| AppError::BadRequest(format!("不支持的文件格式: {}", f)))?,
+        None => import::ImportFormat::sniff(&body),
+    };
+
+    let table = import::read_table(&body, file_format).map_err(AppError::BadRequest)?;
+    let (rows, parse_failures) = import::parse_rows(&table).map_err(AppError::BadRequest)?;
+
+    let result = service::import_schedule(
+        &state.pool,
+        &auth_user.user_id,
+        query.semester_id,
+        rows,
+        parse_failures,
+        query.dry_run,
+        &state.section_times,
+    )
+        .await?;
+
+    let successful_items: Vec<ScheduleItem> = result.successful_items
+        .into_iter()
+        .map(schedule_item_to_proto)
+        .collect();
+
+    let failed_items: Vec<FailedItem> = result.failed_items
+        .into_iter()
+        .map(failed_item_to_proto)
+        .collect();
+
+    let response = AddScheduleItemsResponse {
+        code: OK_CODE,
+        message: if query.dry_run { "预览完成" } else { "导入完成" }.into(),
+        data: Some(AddScheduleItemsData {
+            successful_items,
+            failed_items,
+        }),
+    };
+
+    Ok(format.reply(&response))
 }
 
 /// 更新课表项
 async fn update_schedule_item_handler(
     State(state): State<AppState>,
+    format: AcceptFormat,
     Query(query): Query<ItemIdQuery>,
     auth_user: AuthUser,
-    body: axum::body::Bytes,
+    ProtoOrJson(proto_req): ProtoOrJson<UpdateScheduleItemRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let proto_req = UpdateScheduleItemRequest::decode(body)?;
-
     let input = entity::UpdateScheduleItemInput {
         course_name: proto_req.course_name,
         teacher_name: proto_req.teacher_name,
@@ -254,58 +384,306 @@ async fn update_schedule_item_handler(
 
     let item = service::update_schedule_item(
         &state.pool,
-        auth_user.user_id,
+        &auth_user.user_id,
         query.item_id,
         input,
+        &state.section_times,
     )
         .await?;
 
-    let proto_item = ScheduleItem {
-        id: item.id,
-        source_id: item.source_id,
-        course_name: item.course_name,
-        teacher_name: item.teacher_name,
-        location: item.location,
-        day_of_week: item.day_of_week,
-        start_section: item.start_section,
-        end_section: item.end_section,
-        weeks: item.weeks_range,
-        r#type: item.r#type,
-        credits: item.credits,
-        description: item.description,
-        color_hex: item.color_hex,
-        is_custom: item.is_custom,
-    };
+    let proto_item = schedule_item_to_proto(item);
 
     let response = UpdateScheduleItemResponse {
-        code: 200,
+        code: OK_CODE,
         message: "更新成功".to_string(),
         data: Some(UpdateScheduleItemData { item: Some(proto_item) }),
     };
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "application/x-protobuf")],
-        response.encode_to_vec(),
-    ))
+    Ok(format.reply(&response))
 }
 
 /// 删除课表项
 async fn delete_schedule_item_handler(
     State(state): State<AppState>,
+    format: AcceptFormat,
     Query(query): Query<ItemIdQuery>,
     auth_user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    service::delete_schedule_item(&state.pool, auth_user.user_id, query.item_id).await?;
+    service::delete_schedule_item(&state.pool, &auth_user.user_id, query.item_id).await?;
 
     let response = DeleteScheduleItemResponse {
-        code: 200,
+        code: OK_CODE,
         message: "删除成功".to_string(),
     };
 
+    Ok(format.reply(&response))
+}
+
+/// 生成用户某学期课表的 .ics 文本
+async fn render_schedule_ics(
+    state: &AppState,
+    user_id: &str,
+    semester_id: i64,
+) -> Result<String, AppError> {
+    let semester = service::get_semester(&state.pool, semester_id).await?;
+    let sections = service::resolve_section_times(&state.pool, semester_id, &state.section_times).await?;
+    let items = service::get_user_schedule(&state.pool, user_id, semester_id, None, &state.section_times).await?;
+
+    calendar::build_calendar(&semester, &items, &sections, chrono::Utc::now())
+        .map_err(AppError::InternalError)
+}
+
+/// 下载课表 .ics 文件
+async fn export_schedule_ics_handler(
+    State(state): State<AppState>,
+    Query(query): Query<CalendarQuery>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let ics = render_schedule_ics(&state, &auth_user.user_id, query.semester_id).await?;
+
     Ok((
         StatusCode::OK,
-        [(header::CONTENT_TYPE, "application/x-protobuf")],
-        response.encode_to_vec(),
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"schedule-{}.ics\"", query.semester_id),
+            ),
+        ],
+        ics,
     ))
 }
+
+/// 获取课表订阅链接（手机日历订阅用）
+async fn get_schedule_subscription_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    Query(query): Query<CalendarQuery>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    // 先确认学期存在，避免签发无效链接
+    service::get_semester(&state.pool, query.semester_id).await?;
+
+    let version = service::get_feed_version(&state.pool, &auth_user.user_id).await?;
+    Ok(subscription_response(&state, format, &auth_user.user_id, query.semester_id, version, "成功"))
+}
+
+/// 重置订阅链接：之前发出的所有订阅链接失效（链接泄露时使用），返回该学期的新链接
+async fn rotate_schedule_subscription_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    Query(query): Query<CalendarQuery>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    service::get_semester(&state.pool, query.semester_id).await?;
+
+    let version = service::rotate_feed_version(&state.pool, &auth_user.user_id).await?;
+    Ok(subscription_response(&state, format, &auth_user.user_id, query.semester_id, version, "订阅链接已重置"))
+}
+
+fn subscription_response(
+    state: &AppState,
+    format: AcceptFormat,
+    user_id: &str,
+    semester_id: i64,
+    version: i64,
+    message: &str,
+) -> Response {
+    let token = state.schedule_feed.issue(user_id, semester_id, version);
+    let url = state.schedule_feed.subscription_url(&token);
+
+    let response = GetScheduleSubscriptionResponse {
+        code: OK_CODE,
+        message: message.to_string(),
+        data: Some(GetScheduleSubscriptionData { url, token }),
+    };
+
+    format.reply(&response)
+}
+
+/// 日历应用拉取订阅内容（无需登录，凭签名 token 访问）
+async fn schedule_feed_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let invalid = || AppError::NotFound("订阅链接无效".to_string());
+    let claims = state.schedule_feed.verify(&token).ok_or_else(invalid)?;
+    // 用户重置过订阅链接后，旧版本的 token 不再可用
+    if claims.version != service::get_feed_version(&state.pool, &claims.user_id).await? {
+        return Err(invalid());
+    }
+
+    let ics = render_schedule_ics(&state, &claims.user_id, claims.semester_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CACHE_CONTROL, "private, max-age=900"),
+        ],
+        ics,
+    ))
+}
+
+// ==================== 管理端 ====================
+
+fn semester_to_proto(s: entity::Semester) -> Semester {
+    Semester {
+        id: s.id,
+        name: s.name,
+        start_date: s.start_date,
+        end_date: s.end_date,
+        is_current: s.is_current,
+    }
+}
+
+fn semester_response(format: AcceptFormat, semester: entity::Semester, message: &str) -> Response {
+    let response = SemesterResponse {
+        code: OK_CODE,
+        message: message.to_string(),
+        data: Some(semester_to_proto(semester)),
+    };
+
+    format.reply(&response)
+}
+
+/// 新建学期
+async fn create_semester_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    _admin: RequireRole<Admin>,
+    ProtoOrJson(proto_req): ProtoOrJson<CreateSemesterRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let semester = service::create_semester(
+        &state.pool,
+        entity::SemesterInput {
+            name: proto_req.name,
+            start_date: proto_req.start_date,
+            end_date: proto_req.end_date,
+            is_current: proto_req.is_current,
+        },
+    )
+        .await?;
+
+    Ok(semester_response(format, semester, "创建成功"))
+}
+
+/// 更新学期
+async fn update_semester_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    _admin: RequireRole<Admin>,
+    Path(semester_id): Path<i64>,
+    ProtoOrJson(proto_req): ProtoOrJson<UpdateSemesterRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let semester = service::update_semester(
+        &state.pool,
+        semester_id,
+        entity::UpdateSemesterInput {
+            name: proto_req.name,
+            start_date: proto_req.start_date,
+            end_date: proto_req.end_date,
+        },
+    )
+        .await?;
+
+    Ok(semester_response(format, semester, "更新成功"))
+}
+
+/// 删除学期
+async fn delete_semester_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    _admin: RequireRole<Admin>,
+    Path(semester_id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    service::delete_semester(&state.pool, semester_id).await?;
+
+    let response = DeleteSemesterResponse {
+        code: OK_CODE,
+        message: "删除成功".to_string(),
+    };
+
+    Ok(format.reply(&response))
+}
+
+/// 设置当前学期
+async fn set_current_semester_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    _admin: RequireRole<Admin>,
+    Path(semester_id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let semester = service::set_current_semester(&state.pool, semester_id).await?;
+    Ok(semester_response(format, semester, "设置成功"))
+}
+
+/// 批量导入 / 更新全校课程（CSV 或 protobuf）
+async fn upsert_public_courses_handler(
+    State(state): State<AppState>,
+    format: AcceptFormat,
+    _admin: RequireRole<Admin>,
+    Query(query): Query<UpsertCoursesQuery>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    let is_csv = match query.format.as_deref() {
+        Some(f) if f.eq_ignore_ascii_case("csv") => true,
+        Some(f) if f.eq_ignore_ascii_case("protobuf") => false,
+        Some(f) => return Err(AppError::BadRequest(format!("不支持的文件格式: {}", f))),
+        None => headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/csv")),
+    };
+
+    let (courses, parse_failures) = if is_csv {
+        admin::parse_courses_csv(&body).map_err(AppError::BadRequest)?
+    } else {
+        let proto_req: UpsertPublicCoursesRequest = Format::from_content_type(&headers).decode(&body)?;
+        let courses = proto_req
+            .courses
+            .into_iter()
+            .map(|c| entity::PublicCourseInput {
+                id: c.id,
+                course_name: c.course_name,
+                teacher_name: c.teacher_name,
+                teacher_id: c.teacher_id,
+                location: c.location,
+                day_of_week: c.day_of_week,
+                start_section: c.start_section,
+                end_section: c.end_section,
+                weeks_range: c.weeks_range,
+                r#type: c.r#type,
+                credits: c.credits,
+                description: c.description,
+            })
+            .collect();
+        (courses, Vec::new())
+    };
+
+    let result = service::upsert_public_courses(
+        &state.pool,
+        query.semester_id,
+        courses,
+        parse_failures,
+        &state.section_times,
+    )
+        .await?;
+
+    let response = UpsertPublicCoursesResponse {
+        code: OK_CODE,
+        message: "处理完成".to_string(),
+        data: Some(UpsertPublicCoursesData {
+            inserted: result.inserted,
+            updated: result.updated,
+            failed_items: result
+                .failed_items
+                .into_iter()
+                .map(failed_item_to_proto)
+                .collect(),
+        }),
+    };
+
+    Ok(format.reply(&response))
+}
@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Serialize};
 use sqlx::types::Json;
 
@@ -14,6 +14,15 @@ pub struct SemesterDb {
     pub is_current: bool,
 }
 
+/// 学期作息表数据库实体（section_times 表，每学期每节一行）
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SectionTimeDb {
+    pub semester_id: i64,
+    pub section: i32,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
 /// 全校课程数据库实体
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct PublicCourseDb {
@@ -37,7 +46,7 @@ pub struct PublicCourseDb {
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct ScheduleItemDb {
     pub id: i64,
-    pub user_id: i64,
+    pub user_id: String,
     pub semester_id: i64,
     pub source_id: Option<i64>,
     pub course_name: String,
@@ -133,6 +142,12 @@ pub struct ScheduleItem {
     pub description: Option<String>,
     pub color_hex: String,
     pub is_custom: bool,
+    /// 按学期作息表解析出的上课时间（HH:MM）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    /// 按学期作息表解析出的下课时间（HH:MM）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
 }
 
 impl From<ScheduleItemDb> for ScheduleItem {
@@ -152,6 +167,32 @@ impl From<ScheduleItemDb> for ScheduleItem {
             description: db.description,
             color_hex: db.color_hex,
             is_custom: db.is_custom,
+            start_time: None,
+            end_time: None,
+        }
+    }
+}
+
+impl ScheduleItem {
+    /// 由新增输入构造（上下课时间稍后按作息表解析）
+    pub fn from_input(id: i64, input: ScheduleItemInput) -> Self {
+        Self {
+            id,
+            source_id: input.source_id,
+            course_name: input.course_name,
+            teacher_name: input.teacher_name,
+            location: input.location,
+            day_of_week: input.day_of_week,
+            start_section: input.start_section,
+            end_section: input.end_section,
+            weeks_range: input.weeks,
+            r#type: input.r#type,
+            credits: input.credits,
+            description: input.description,
+            color_hex: input.color_hex,
+            is_custom: input.is_custom,
+            start_time: None,
+            end_time: None,
         }
     }
 }
@@ -192,6 +233,40 @@ pub struct UpdateScheduleItemInput {
     pub color_hex: Option<String>,
 }
 
+/// 新建学期输入
+#[derive(Debug, Clone)]
+pub struct SemesterInput {
+    pub name: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub is_current: bool,
+}
+
+/// 更新学期输入
+#[derive(Debug, Clone)]
+pub struct UpdateSemesterInput {
+    pub name: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+/// 全校课程导入 / 更新输入；带 id 时按 id 更新，否则按课程名、教师、时间匹配已有课程
+#[derive(Debug, Clone)]
+pub struct PublicCourseInput {
+    pub id: Option<i64>,
+    pub course_name: String,
+    pub teacher_name: String,
+    pub teacher_id: Option<i64>,
+    pub location: String,
+    pub day_of_week: i32,
+    pub start_section: i32,
+    pub end_section: i32,
+    pub weeks_range: Vec<i32>,
+    pub r#type: String,
+    pub credits: Option<i32>,
+    pub description: Option<String>,
+}
+
 /// 获取全校课程查询参数
 #[derive(Debug, Clone)]
 pub struct GetCoursesParams {
@@ -216,6 +291,17 @@ pub struct Pagination {
 pub struct FailedItem {
     pub course_name: String,
     pub error_message: String,
+    /// 时间冲突时，与之冲突的课程名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict_with: Option<String>,
+}
+
+/// 全校课程批量导入结果
+#[derive(Debug, Clone)]
+pub struct UpsertCoursesResult {
+    pub inserted: i32,
+    pub updated: i32,
+    pub failed_items: Vec<FailedItem>,
 }
 
 /// 批量添加结果
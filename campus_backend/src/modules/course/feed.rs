@@ -0,0 +1,122 @@
+// src/modules/course/feed.rs
+//
+// 课表订阅链接：手机日历应用订阅时无法携带 Authorization 头，
+// 因此把用户和学期签进 URL 里的 token。
+//
+// token 格式：`{user}.{semester_id}.{version}.{signature}`
+// - user：user_id 的 base64url 编码（user_id 本身可能包含 `.`）
+// - version：用户的订阅链接版本（schedule_feeds 表），重置链接时加一，旧 token 随之失效
+// - signature：HMAC-SHA256(secret, "schedule-feed:{user_id}:{semester_id}:{version}")，base64url 编码
+//
+// secret 读取 SCHEDULE_FEED_SECRET，未配置时从 JWT_SECRET 派生，不与登录 token 共用同一个密钥。
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::common::secrets;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 订阅地址的路径前缀，完整地址为 `{base_url}{FEED_PATH}/{token}.ics`
+pub const FEED_PATH: &str = "/api/v1/schedule/feed";
+
+/// token 中携带的信息；version 需要与数据库中用户当前的版本比对
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedClaims {
+    pub user_id: String,
+    pub semester_id: i64,
+    pub version: i64,
+}
+
+#[derive(Clone)]
+pub struct FeedSigner {
+    secret: Vec<u8>,
+    /// 对外访问地址（如 https://campus.example.com），用于拼接订阅链接
+    base_url: Option<String>,
+}
+
+impl FeedSigner {
+    pub fn new(secret: impl Into<Vec<u8>>, base_url: Option<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            base_url: base_url.map(|u| u.trim_end_matches('/').to_string()),
+        }
+    }
+
+    /// 读取 SCHEDULE_FEED_SECRET（未配置时从 JWT_SECRET 派生）和 PUBLIC_BASE_URL
+    pub fn from_env() -> Self {
+        let secret = secrets::signing_key("SCHEDULE_FEED_SECRET", "schedule-feed");
+
+        Self::new(secret, std::env::var("PUBLIC_BASE_URL").ok())
+    }
+
+    fn mac(&self, user_id: &str, semester_id: i64, version: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts any key length");
+        mac.update(format!("schedule-feed:{}:{}:{}", user_id, semester_id, version).as_bytes());
+        mac
+    }
+
+    pub fn issue(&self, user_id: &str, semester_id: i64, version: i64) -> String {
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(user_id, semester_id, version).finalize().into_bytes());
+        format!("{}.{}.{}.{}", URL_SAFE_NO_PAD.encode(user_id), semester_id, version, signature)
+    }
+
+    /// 校验 token 签名；允许带 `.ics` 后缀。是否已被重置由调用方比对 version
+    pub fn verify(&self, token: &str) -> Option<FeedClaims> {
+        let token = token.trim().trim_end_matches(".ics");
+        let mut parts = token.splitn(4, '.');
+        let user = parts.next()?;
+        let semester_id = parts.next()?.parse::<i64>().ok()?;
+        let version = parts.next()?.parse::<i64>().ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(parts.next()?).ok()?;
+
+        let user_id = String::from_utf8(URL_SAFE_NO_PAD.decode(user).ok()?).ok()?;
+        self.mac(&user_id, semester_id, version)
+            .verify_slice(&signature)
+            .ok()
+            .map(|_| FeedClaims { user_id, semester_id, version })
+    }
+
+    /// 订阅链接；配置了 PUBLIC_BASE_URL 时返回 webcal:// 绝对地址，否则返回路径
+    pub fn subscription_url(&self, token: &str) -> String {
+        let path = format!("{}/{}.ics", FEED_PATH, token);
+        match &self.base_url {
+            Some(base) => {
+                let host = base
+                    .strip_prefix("https://")
+                    .or_else(|| base.strip_prefix("http://"))
+                    .unwrap_or(base);
+                format!("webcal://{}{}", host, path)
+            }
+            None => path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_token_roundtrip() {
+        let signer = FeedSigner::new("secret", Some("https://campus.example.com/".into()));
+        let token = signer.issue("user.1", 3, 2);
+        let claims = FeedClaims { user_id: "user.1".into(), semester_id: 3, version: 2 };
+
+        assert_eq!(signer.verify(&token), Some(claims.clone()));
+        assert_eq!(signer.verify(&format!("{}.ics", token)), Some(claims));
+        assert_eq!(
+            signer.subscription_url(&token),
+            format!("webcal://campus.example.com/api/v1/schedule/feed/{}.ics", token)
+        );
+
+        // 改学期、改版本、换密钥都不能通过
+        let (user, _) = token.split_once('.').unwrap();
+        let sig = token.rsplit('.').next().unwrap();
+        assert_eq!(signer.verify(&format!("{}.4.2.{}", user, sig)), None);
+        assert_eq!(signer.verify(&format!("{}.3.3.{}", user, sig)), None);
+        assert_eq!(FeedSigner::new("other", None).verify(&token), None);
+    }
+}
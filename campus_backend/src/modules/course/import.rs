@@ -0,0 +1,574 @@
+// src/modules/course/import.rs
+//
+// 从教务系统导出的课表文件导入课表：
+// - 支持 CSV（UTF-8 / GBK）、XLS / XLSX，以及 HTML 表格（很多教务系统导出的 .xls 实际上是 HTML）
+// - 先把文件解析成二维表格，再按表头识别列，每行解析成一个或多个 ImportedRow
+// - 同一行的「上课时间」里有多段时间（如 `周一第1,2节{1-16周};周三第3,4节{1-16周}`）时拆成多条
+//
+// 行的解析结果再交给 service::import_schedule 匹配 public_courses 并写入课表。
+
+use std::io::Cursor;
+
+use calamine::Reader;
+use scraper::{Html, Selector};
+
+use super::entity::FailedItem;
+
+/// 导入的课表项没有颜色时按课程名从调色板里选一个
+const PALETTE: [&str; 8] = [
+    "#5B8FF9", "#5AD8A6", "#F6BD16", "#E8684A", "#6DC8EC", "#9270CA", "#FF9D4D", "#269A99",
+];
+
+/// 周次上限：超过的周次一律视为无法识别，避免 `1-2000000000周` 这样的区间被展开
+pub const MAX_WEEK: i32 = 30;
+
+/// 文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    /// XLS / XLSX
+    Excel,
+    Html,
+}
+
+impl ImportFormat {
+    /// 解析 `?format=` 参数
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "xls" | "xlsx" | "excel" => Some(Self::Excel),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// 根据文件内容判断格式：OLE2 / ZIP 头为 Excel，`<` 开头为 HTML，其余按 CSV 处理
+    pub fn sniff(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0xD0, 0xCF, 0x11, 0xE0]) || bytes.starts_with(b"PK\x03\x04") {
+            return Self::Excel;
+        }
+        let text = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        match text.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'<') => Self::Html,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// 从文件中解析出的一条课表项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedRow {
+    pub course_name: String,
+    pub teacher_name: Option<String>,
+    pub location: Option<String>,
+    pub day_of_week: i32,
+    pub start_section: i32,
+    pub end_section: i32,
+    pub weeks: Vec<i32>,
+    pub r#type: Option<String>,
+    pub credits: Option<i32>,
+}
+
+/// 用于匹配的全校课程
+#[derive(Debug, Clone)]
+pub struct CourseCandidate {
+    pub id: i64,
+    pub course_name: String,
+    pub teacher_name: String,
+    pub day_of_week: i32,
+    pub start_section: i32,
+    pub end_section: i32,
+}
+
+// ==================== 文件 -> 表格 ====================
+
+/// 把文件解析成二维表格
+pub fn read_table(bytes: &[u8], format: ImportFormat) -> Result<Vec<Vec<String>>, String> {
+    match format {
+        ImportFormat::Csv => read_csv(&decode_text(bytes)),
+        ImportFormat::Excel => read_excel(bytes),
+        ImportFormat::Html => read_html(&decode_text(bytes)),
+    }
+}
+
+/// 优先按 UTF-8 解码，失败时按 GBK 解码（国内教务系统常见）
+fn decode_text(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => encoding_rs::GBK.decode(bytes).0.into_owned(),
+    }
+}
+
+fn read_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|r| r.iter().map(clean_cell).collect())
+                .map_err(|e| format!("CSV 解析失败: {}", e))
+        })
+        .collect()
+}
+
+fn read_excel(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook = calamine::open_workbook_auto_from_rs(Cursor::new(bytes.to_vec()))
+        .map_err(|e| format!("Excel 解析失败: {}", e))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or("Excel 文件中没有工作表")?
+        .map_err(|e| format!("Excel 解析失败: {}", e))?;
+
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(|cell| clean_cell(&cell.to_string())).collect())
+        .collect())
+}
+
+/// 取包含课程名列的第一个 <table>
+fn read_html(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let document = Html::parse_document(text);
+    let table_sel = Selector::parse("table").expect("valid selector");
+    let row_sel = Selector::parse("tr").expect("valid selector");
+    let cell_sel = Selector::parse("th, td").expect("valid selector");
+
+    for table in document.select(&table_sel) {
+        let rows: Vec<Vec<String>> = table
+            .select(&row_sel)
+            .map(|tr| {
+                tr.select(&cell_sel)
+                    .map(|cell| clean_cell(&cell.text().collect::<Vec<_>>().join("\n")))
+                    .collect()
+            })
+            .collect();
+
+        if find_header(&rows).is_some() {
+            return Ok(rows);
+        }
+    }
+
+    Err("HTML 中没有找到课表表格".into())
+}
+
+/// 去掉首尾空白和 &nbsp;，每行单独 trim
+fn clean_cell(s: &str) -> String {
+    s.replace('\u{a0}', " ")
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ==================== 表格 -> 课表项 ====================
+
+/// 各字段所在的列
+#[derive(Debug, Default)]
+struct Columns {
+    course_name: usize,
+    teacher: Option<usize>,
+    location: Option<usize>,
+    day: Option<usize>,
+    sections: Option<usize>,
+    start_section: Option<usize>,
+    end_section: Option<usize>,
+    weeks: Option<usize>,
+    time: Option<usize>,
+    r#type: Option<usize>,
+    credits: Option<usize>,
+}
+
+fn column(header: &[String], aliases: &[&str]) -> Option<usize> {
+    header.iter().position(|h| {
+        let h: String = h.chars().filter(|c| !c.is_whitespace()).collect();
+        aliases.contains(&h.as_str())
+    })
+}
+
+/// 找到表头所在行（前 10 行里第一个包含课程名列的行）
+fn find_header(rows: &[Vec<String>]) -> Option<(usize, Columns)> {
+    rows.iter().take(10).enumerate().find_map(|(idx, header)| {
+        let columns = Columns {
+            course_name: column(header, &["课程名称", "课程名", "课程"])?,
+            teacher: column(header, &["教师", "任课教师", "授课教师", "上课教师", "老师"]),
+            location: column(header, &["上课地点", "地点", "教室", "上课教室"]),
+            day: column(header, &["星期", "星期几", "上课星期"]),
+            sections: column(header, &["节次", "上课节次"]),
+            start_section: column(header, &["开始节次", "起始节次"]),
+            end_section: column(header, &["结束节次"]),
+            weeks: column(header, &["周次", "上课周次", "起止周", "周数"]),
+            time: column(header, &["上课时间", "时间"]),
+            r#type: column(header, &["课程性质", "课程类型", "类型"]),
+            credits: column(header, &["学分"]),
+        };
+
+        let has_split = columns.day.is_some()
+            && (columns.sections.is_some() || columns.start_section.is_some())
+            && columns.weeks.is_some();
+        (has_split || columns.time.is_some()).then_some((idx, columns))
+    })
+}
+
+/// 把表格解析成课表项；解析失败的行放进 FailedItem，不影响其他行
+pub fn parse_rows(rows: &[Vec<String>]) -> Result<(Vec<ImportedRow>, Vec<FailedItem>), String> {
+    let (header_idx, columns) = find_header(rows)
+        .ok_or("没有识别到表头，需要包含「课程名称」以及「上课时间」或「星期 / 节次 / 周次」列")?;
+
+    let mut parsed = Vec::new();
+    let mut failed = Vec::new();
+
+    for (offset, row) in rows[header_idx + 1..].iter().enumerate() {
+        let cell = |idx: Option<usize>| {
+            idx.and_then(|i| row.get(i))
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+        };
+
+        let Some(course_name) = cell(Some(columns.course_name)) else {
+            continue;
+        };
+        let line = header_idx + offset + 2;
+
+        match parse_row(&columns, &cell) {
+            Ok(times) => {
+                for (day_of_week, start_section, end_section, weeks) in times {
+                    parsed.push(ImportedRow {
+                        course_name: course_name.to_string(),
+                        teacher_name: cell(columns.teacher).map(str::to_string),
+                        location: cell(columns.location).map(str::to_string),
+                        day_of_week,
+                        start_section,
+                        end_section,
+                        weeks,
+                        r#type: cell(columns.r#type).map(str::to_string),
+                        credits: cell(columns.credits)
+                            .and_then(|c| c.parse::<f64>().ok())
+                            .map(|c| c.round() as i32),
+                    });
+                }
+            }
+            Err(msg) => failed.push(FailedItem {
+                course_name: course_name.to_string(),
+                error_message: format!("第 {} 行: {}", line, msg),
+                conflict_with: None,
+            }),
+        }
+    }
+
+    Ok((parsed, failed))
+}
+
+type TimeSlot = (i32, i32, i32, Vec<i32>);
+
+fn parse_row<'a>(
+    columns: &Columns,
+    cell: &dyn Fn(Option<usize>) -> Option<&'a str>,
+) -> Result<Vec<TimeSlot>, String> {
+    // 优先使用单独的 星期 / 节次 / 周次 列
+    if let (Some(day), Some(weeks)) = (cell(columns.day), cell(columns.weeks)) {
+        let day = parse_day(day).ok_or_else(|| format!("无法识别星期: {}", day))?;
+        let (start, end) = match (cell(columns.sections), cell(columns.start_section), cell(columns.end_section)) {
+            (Some(sections), _, _) => parse_sections(sections),
+            (None, Some(start), Some(end)) => parse_sections(&format!("{}-{}", start, end)),
+            (None, Some(start), None) => parse_sections(start),
+            _ => None,
+        }
+        .ok_or("无法识别节次")?;
+        let weeks = parse_weeks(weeks).ok_or_else(|| format!("无法识别周次: {}", weeks))?;
+        return Ok(vec![(day, start, end, weeks)]);
+    }
+
+    let time = cell(columns.time).ok_or("缺少上课时间")?;
+    time.split([';', '；', '\n'])
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| parse_time_text(t).ok_or_else(|| format!("无法识别上课时间: {}", t)))
+        .collect()
+}
+
+/// 星期：`星期一` / `周一` / `一` / `1` / `星期日`
+pub fn parse_day(s: &str) -> Option<i32> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix("星期")
+        .or_else(|| s.strip_prefix("礼拜"))
+        .or_else(|| s.strip_prefix("周"))
+        .unwrap_or(s);
+    day_from_char(s.chars().next()?)
+}
+
+fn day_from_char(c: char) -> Option<i32> {
+    match c {
+        '一' | '1' => Some(1),
+        '二' | '2' => Some(2),
+        '三' | '3' => Some(3),
+        '四' | '4' => Some(4),
+        '五' | '5' => Some(5),
+        '六' | '6' => Some(6),
+        '日' | '天' | '7' => Some(7),
+        _ => None,
+    }
+}
+
+fn numbers(s: &str) -> Vec<i32> {
+    s.split(|c: char| !c.is_ascii_digit())
+        .filter_map(|n| n.parse().ok())
+        .collect()
+}
+
+/// 节次：`3-4` / `第3-4节` / `第3,4节` / `3`，返回 (开始, 结束)
+pub fn parse_sections(s: &str) -> Option<(i32, i32)> {
+    let nums = numbers(s);
+    Some((*nums.iter().min()?, *nums.iter().max()?))
+}
+
+/// 周次：`1-16周` / `1-8,10-16` / `1-15(单)` / `2-16双周` / `3,5,7`
+///
+/// 单双周标记写在某一段后面时只作用于该段；只有一处标记且写在末尾时作用于所有段。
+/// 起始周大于结束周、周次为 0 或超过 MAX_WEEK 时返回 None。
+pub fn parse_weeks(s: &str) -> Option<Vec<i32>> {
+    let segments: Vec<&str> = s
+        .split([',', '，', '、', ' '])
+        .map(str::trim)
+        .filter(|seg| !seg.is_empty())
+        .collect();
+
+    let parity_of = |seg: &str| {
+        if seg.contains('单') {
+            Some(1)
+        } else if seg.contains('双') {
+            Some(0)
+        } else {
+            None
+        }
+    };
+    let marked: Vec<_> = segments.iter().filter_map(|seg| parity_of(seg)).collect();
+    let global = match (marked.as_slice(), segments.last().and_then(|seg| parity_of(seg))) {
+        ([only], Some(_)) => Some(*only),
+        _ => None,
+    };
+
+    let mut weeks = Vec::new();
+    for seg in &segments {
+        let parity = parity_of(seg).or(global);
+        let (start, end) = match numbers(seg).as_slice() {
+            [] => continue,
+            [single] => (*single, *single),
+            [start, end, ..] => (*start, *end),
+        };
+        // 先检查区间再展开
+        if start < 1 || start > end || end > MAX_WEEK {
+            return None;
+        }
+        weeks.extend((start..=end).filter(|w| parity.is_none_or(|p| w % 2 == p)));
+    }
+
+    weeks.sort_unstable();
+    weeks.dedup();
+    (!weeks.is_empty()).then_some(weeks)
+}
+
+/// 合并在一起的上课时间：`周一第1,2节{第1-16周}` / `星期三 3-4节 1-15单周`
+pub fn parse_time_text(s: &str) -> Option<TimeSlot> {
+    let chars: Vec<char> = s.chars().collect();
+
+    // 星期：`星期X` 或 `周X`
+    let day = chars.windows(2).enumerate().find_map(|(i, w)| {
+        let after_prefix = (w[0] == '周') || (i > 0 && chars[i - 1] == '星' && w[0] == '期');
+        if after_prefix { day_from_char(w[1]) } else { None }
+    })?;
+
+    // 节次：`节` 前面的数字、`-`、`,`
+    let section_end = chars.iter().position(|c| *c == '节')?;
+    let section_text: String = take_back(&chars[..section_end], "0123456789-,，");
+    let (start, end) = parse_sections(&section_text)?;
+
+    // 周次：花括号里的内容，或者最后一个 `周` 前面的数字和单双周标记
+    let weeks_text = match (s.find('{'), s.rfind('}')) {
+        (Some(open), Some(close)) if open < close => s[open + 1..close].to_string(),
+        _ => {
+            let last = chars.iter().rposition(|c| *c == '周')?;
+            let mut text = take_back(&chars[..last], "0123456789-,，单双()（）");
+            if chars.get(last + 1).is_some_and(|c| matches!(c, '(' | '（')) {
+                text.extend(chars[last + 1..].iter().take_while(|c| !matches!(c, ')' | '）')));
+            }
+            text
+        }
+    };
+    let weeks = parse_weeks(&weeks_text)?;
+
+    Some((day, start, end, weeks))
+}
+
+/// 从末尾往前取属于 allowed 的连续字符
+fn take_back(chars: &[char], allowed: &str) -> String {
+    let start = chars
+        .iter()
+        .rposition(|c| !allowed.contains(*c))
+        .map_or(0, |i| i + 1);
+    chars[start..].iter().collect()
+}
+
+// ==================== 匹配全校课程 ====================
+
+/// 按课程名、星期、节次匹配全校课程；有多个同名课程时再按教师区分
+pub fn match_public_course(candidates: &[CourseCandidate], row: &ImportedRow) -> Option<i64> {
+    let same_slot: Vec<&CourseCandidate> = candidates
+        .iter()
+        .filter(|c| {
+            c.course_name == row.course_name
+                && c.day_of_week == row.day_of_week
+                && c.start_section == row.start_section
+                && c.end_section == row.end_section
+        })
+        .collect();
+
+    match row.teacher_name.as_deref() {
+        Some(teacher) => same_slot
+            .iter()
+            .find(|c| c.teacher_name == teacher)
+            .or_else(|| same_slot.first().filter(|_| same_slot.len() == 1))
+            .map(|c| c.id),
+        None if same_slot.len() == 1 => Some(same_slot[0].id),
+        None => None,
+    }
+}
+
+/// 同名课程使用同一个颜色
+pub fn default_color(course_name: &str) -> String {
+    let hash = course_name
+        .bytes()
+        .fold(0usize, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+    PALETTE[hash % PALETTE.len()].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|r| r.iter().map(|c| c.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_weeks() {
+        assert_eq!(parse_weeks("1-4周"), Some(vec![1, 2, 3, 4]));
+        assert_eq!(parse_weeks("1-3,6"), Some(vec![1, 2, 3, 6]));
+        assert_eq!(parse_weeks("1-7(单)"), Some(vec![1, 3, 5, 7]));
+        assert_eq!(parse_weeks("2-8双周"), Some(vec![2, 4, 6, 8]));
+        assert_eq!(parse_weeks("1-5单,8-9"), Some(vec![1, 3, 5, 8, 9]));
+        assert_eq!(parse_weeks("第1-2周"), Some(vec![1, 2]));
+        assert_eq!(parse_weeks("无"), None);
+        assert_eq!(parse_weeks("0-2"), None);
+    }
+
+    #[test]
+    fn test_parse_weeks_rejects_bad_ranges() {
+        // 超大区间不展开
+        assert_eq!(parse_weeks("1-2000000000周"), None);
+        assert_eq!(parse_weeks("1-31"), None);
+        assert_eq!(parse_weeks("1-16,99"), None);
+        assert_eq!(parse_weeks("1-30").map(|w| w.len()), Some(30));
+        // 起止颠倒
+        assert_eq!(parse_weeks("16-1周"), None);
+        assert_eq!(parse_weeks("1-4,8-5"), None);
+    }
+
+    #[test]
+    fn test_parse_time_text() {
+        assert_eq!(
+            parse_time_text("周一第1,2节{第1-4周}"),
+            Some((1, 1, 2, vec![1, 2, 3, 4]))
+        );
+        assert_eq!(
+            parse_time_text("星期三 3-4节 1-7单周"),
+            Some((3, 3, 4, vec![1, 3, 5, 7]))
+        );
+        assert_eq!(parse_time_text("星期日第9-11节 2-4周(双)"), Some((7, 9, 11, vec![2, 4])));
+        assert_eq!(parse_time_text("待定"), None);
+    }
+
+    #[test]
+    fn test_parse_rows_with_split_columns() {
+        let rows = table(&[
+            &["2024 春季课表"],
+            &["课程名称", "任课教师", "星期", "节次", "周次", "上课地点", "学分"],
+            &["高等数学", "张老师", "周二", "1-2", "1-16周", "教一-101", "4.0"],
+            &["", "", "", "", "", "", ""],
+            &["体育", "李老师", "八", "3-4", "1-16", "操场", "1"],
+        ]);
+
+        let (parsed, failed) = parse_rows(&rows).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].day_of_week, 2);
+        assert_eq!(parsed[0].weeks.len(), 16);
+        assert_eq!(parsed[0].credits, Some(4));
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].error_message.starts_with("第 5 行"));
+    }
+
+    #[test]
+    fn test_parse_html_with_multiple_times() {
+        let html = "<html><body><table><tr><td>无关表格</td></tr></table>\
+            <table><tr><th>课程名</th><th>教师</th><th>上课时间</th><th>教室</th></tr>\
+            <tr><td>线性代数</td><td>王老师</td><td>周一第1,2节{第1-8周};周三第3,4节{第1-8周}</td><td>教二&nbsp;201</td></tr>\
+            </table></body></html>";
+
+        let format = ImportFormat::sniff(html.as_bytes());
+        assert_eq!(format, ImportFormat::Html);
+
+        let (parsed, failed) = parse_rows(&read_table(html.as_bytes(), format).unwrap()).unwrap();
+        assert!(failed.is_empty());
+        assert_eq!(parsed.len(), 2);
+        assert_eq!((parsed[1].day_of_week, parsed[1].start_section), (3, 3));
+        assert_eq!(parsed[0].location.as_deref(), Some("教二 201"));
+    }
+
+    #[test]
+    fn test_parse_gbk_csv() {
+        let (gbk, _, _) = encoding_rs::GBK.encode("课程名称,星期,开始节次,结束节次,周次\n大学英语,5,3,4,1-2\n");
+        let (parsed, _) = parse_rows(&read_table(&gbk, ImportFormat::sniff(&gbk)).unwrap()).unwrap();
+        assert_eq!(parsed[0].course_name, "大学英语");
+        assert_eq!((parsed[0].start_section, parsed[0].end_section), (3, 4));
+    }
+
+    #[test]
+    fn test_match_public_course() {
+        let candidate = |id, teacher: &str| CourseCandidate {
+            id,
+            course_name: "高等数学".into(),
+            teacher_name: teacher.into(),
+            day_of_week: 2,
+            start_section: 1,
+            end_section: 2,
+        };
+        let candidates = vec![candidate(1, "张老师"), candidate(2, "赵老师")];
+        let mut row = ImportedRow {
+            course_name: "高等数学".into(),
+            teacher_name: Some("赵老师".into()),
+            location: None,
+            day_of_week: 2,
+            start_section: 1,
+            end_section: 2,
+            weeks: vec![1],
+            r#type: None,
+            credits: None,
+        };
+
+        assert_eq!(match_public_course(&candidates, &row), Some(2));
+
+        // 没有教师信息且有多个候选时不猜
+        row.teacher_name = None;
+        assert_eq!(match_public_course(&candidates, &row), None);
+
+        row.start_section = 3;
+        assert_eq!(match_public_course(&candidates[..1], &row), None);
+    }
+}
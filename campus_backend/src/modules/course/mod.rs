@@ -1,5 +1,10 @@
+pub mod admin;
+pub mod calendar;
+pub mod conflict;
 pub mod controller;
 pub mod entity;
+pub mod feed;
+pub mod import;
 pub mod service;
 mod proto;
 
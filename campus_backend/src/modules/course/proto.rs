@@ -0,0 +1,263 @@
+// src/modules/course/proto.rs
+//
+// build.rs 由 proto/course.proto 生成的消息类型，以及 pbjson 生成的 serde 实现
+
+include!(concat!(env!("OUT_DIR"), "/course.rs"));
+include!(concat!(env!("OUT_DIR"), "/course.serde.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    fn roundtrip<M: Message + Default + PartialEq + std::fmt::Debug>(msg: M) {
+        let decoded = M::decode(msg.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    fn semester() -> Semester {
+        Semester {
+            id: 1,
+            name: "2024 春".into(),
+            start_date: "2024-02-26".into(),
+            end_date: "2024-07-05".into(),
+            is_current: true,
+        }
+    }
+
+    fn schedule_item() -> ScheduleItem {
+        ScheduleItem {
+            id: 10,
+            source_id: Some(3),
+            course_name: "高等数学".into(),
+            teacher_name: Some("张老师".into()),
+            location: None,
+            day_of_week: 1,
+            start_section: 1,
+            end_section: 2,
+            weeks: vec![1, 2, 3],
+            r#type: Some("必修".into()),
+            credits: Some(4),
+            description: None,
+            color_hex: "#5B8FF9".into(),
+            is_custom: false,
+            start_time: Some("08:00".into()),
+            end_time: Some("09:40".into()),
+        }
+    }
+
+    fn public_course() -> PublicCourse {
+        PublicCourse {
+            id: 3,
+            course_name: "高等数学".into(),
+            teacher_name: "张老师".into(),
+            teacher_id: Some(100),
+            location: "教一-101".into(),
+            day_of_week: 1,
+            start_section: 1,
+            end_section: 2,
+            weeks_range: vec![1, 2, 3],
+            r#type: "必修".into(),
+            credits: Some(4),
+            description: Some("工科数学".into()),
+        }
+    }
+
+    fn failed_item() -> FailedItem {
+        FailedItem {
+            course_name: "线性代数".into(),
+            error_message: "与「高等数学」时间冲突".into(),
+            conflict_with: Some("高等数学".into()),
+        }
+    }
+
+    #[test]
+    fn test_semester_messages_roundtrip() {
+        roundtrip(GetSemestersResponse {
+            code: 200,
+            message: "成功".into(),
+            data: Some(GetSemestersData { semesters: vec![semester()] }),
+        });
+        roundtrip(GetSectionTimesResponse {
+            code: 200,
+            message: "成功".into(),
+            data: Some(GetSectionTimesData {
+                semester_id: 1,
+                is_default: true,
+                section_times: vec![SectionTime {
+                    section: 1,
+                    start_time: "08:00".into(),
+                    end_time: "08:45".into(),
+                }],
+            }),
+        });
+        roundtrip(CreateSemesterRequest {
+            name: "2024 秋".into(),
+            start_date: "2024-09-02".into(),
+            end_date: "2025-01-17".into(),
+            is_current: false,
+        });
+        roundtrip(UpdateSemesterRequest {
+            name: Some(String::new()),
+            start_date: None,
+            end_date: Some("2025-01-10".into()),
+        });
+        roundtrip(SemesterResponse {
+            code: 200,
+            message: "创建成功".into(),
+            data: Some(semester()),
+        });
+        roundtrip(DeleteSemesterResponse { code: 200, message: "删除成功".into() });
+    }
+
+    #[test]
+    fn test_public_course_messages_roundtrip() {
+        roundtrip(GetCoursesQuery {
+            semester_id: Some(1),
+            name: Some("数学".into()),
+            teacher: None,
+            page: Some(1),
+            page_size: None,
+        });
+        roundtrip(GetPublicCoursesResponse {
+            code: 200,
+            message: "成功".into(),
+            data: Some(GetPublicCoursesData {
+                list: vec![public_course()],
+                pagination: Some(Pagination { total: 1, page: 1, page_size: 20, pages: 1 }),
+            }),
+        });
+        roundtrip(UpsertPublicCoursesRequest {
+            courses: vec![PublicCourseInput {
+                id: None,
+                course_name: "高等数学".into(),
+                teacher_name: "张老师".into(),
+                teacher_id: None,
+                location: "教一-101".into(),
+                day_of_week: 1,
+                start_section: 1,
+                end_section: 2,
+                weeks_range: vec![1, 3, 5],
+                r#type: "必修".into(),
+                credits: Some(0),
+                description: None,
+            }],
+        });
+        roundtrip(UpsertPublicCoursesResponse {
+            code: 200,
+            message: "处理完成".into(),
+            data: Some(UpsertPublicCoursesData {
+                inserted: 1,
+                updated: 2,
+                failed_items: vec![failed_item()],
+            }),
+        });
+    }
+
+    #[test]
+    fn test_schedule_messages_roundtrip() {
+        roundtrip(GetScheduleQuery { semester_id: 1, week: Some(3) });
+        roundtrip(ItemIdQuery { item_id: 10 });
+        roundtrip(GetScheduleResponse {
+            code: 200,
+            message: "成功".into(),
+            data: Some(GetScheduleData { items: vec![schedule_item()] }),
+        });
+        roundtrip(AddScheduleItemsRequest {
+            semester_id: 1,
+            items: vec![ScheduleItemInput {
+                source_id: None,
+                course_name: "自习".into(),
+                teacher_name: None,
+                location: Some("图书馆".into()),
+                day_of_week: 6,
+                start_section: 1,
+                end_section: 4,
+                weeks: vec![1, 2],
+                r#type: None,
+                credits: None,
+                description: Some("期末复习".into()),
+                color_hex: "#FFFFFF".into(),
+                is_custom: true,
+            }],
+        });
+        roundtrip(AddScheduleItemsResponse {
+            code: 200,
+            message: "处理完成".into(),
+            data: Some(AddScheduleItemsData {
+                successful_items: vec![schedule_item()],
+                failed_items: vec![failed_item()],
+            }),
+        });
+        roundtrip(UpdateScheduleItemResponse {
+            code: 200,
+            message: "更新成功".into(),
+            data: Some(UpdateScheduleItemData { item: Some(schedule_item()) }),
+        });
+        roundtrip(DeleteScheduleItemResponse { code: 200, message: "删除成功".into() });
+    }
+
+    #[test]
+    fn test_update_request_keeps_field_presence() {
+        // 显式设置为零值的字段解码后仍是 Some，未设置的字段仍是 None
+        let req = UpdateScheduleItemRequest {
+            credits: Some(0),
+            location: Some(String::new()),
+            day_of_week: Some(3),
+            ..Default::default()
+        };
+        roundtrip(req.clone());
+
+        let decoded = UpdateScheduleItemRequest::decode(req.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded.credits, Some(0));
+        assert_eq!(decoded.location, Some(String::new()));
+        assert_eq!(decoded.course_name, None);
+        assert_eq!(decoded.start_section, None);
+        assert!(decoded.weeks.is_empty());
+
+        // 空请求不修改任何字段
+        let empty = UpdateScheduleItemRequest::decode(&[][..]).unwrap();
+        assert_eq!(empty, UpdateScheduleItemRequest::default());
+        assert_eq!(empty.credits, None);
+    }
+
+    #[test]
+    fn test_json_mapping() {
+        // proto3 JSON：字段名 camelCase，int64 输出为字符串，未设置的 optional 字段省略
+        let json = serde_json::to_value(schedule_item()).unwrap();
+        assert_eq!(json["id"], "10");
+        assert_eq!(json["sourceId"], "3");
+        assert_eq!(json["courseName"], "高等数学");
+        assert_eq!(json["dayOfWeek"], 1);
+        assert!(json.get("location").is_none());
+
+        // 解析时 snake_case 和 camelCase 都接受，int64 可以是数字也可以是字符串
+        let req: AddScheduleItemsRequest = serde_json::from_str(
+            r##"{"semester_id": 1, "items": [{"courseName": "自习", "dayOfWeek": 3, "start_section": 1, "endSection": 2, "weeks": [1], "colorHex": "#fff", "isCustom": true}]}"##,
+        )
+        .unwrap();
+        assert_eq!(req.semester_id, 1);
+        assert_eq!(req.items[0].course_name, "自习");
+        assert_eq!(req.items[0].end_section, 2);
+        assert_eq!(req.items[0].source_id, None);
+
+        let back: ScheduleItem = serde_json::from_value(json).unwrap();
+        assert_eq!(back, schedule_item());
+    }
+
+    #[test]
+    fn test_query_messages_from_query_string() {
+        use axum::{extract::Query, http::Uri};
+
+        let uri: Uri = "/api/v1/courses?semester_id=1&page=2&teacher=%E5%BC%A0".parse().unwrap();
+        let Query(query) = Query::<GetCoursesQuery>::try_from_uri(&uri).unwrap();
+        assert_eq!(query.semester_id, Some(1));
+        assert_eq!(query.page, Some(2));
+        assert_eq!(query.teacher.as_deref(), Some("张"));
+        assert_eq!(query.page_size, None);
+
+        let uri: Uri = "/api/v1/schedule?semesterId=3&week=5".parse().unwrap();
+        let Query(query) = Query::<GetScheduleQuery>::try_from_uri(&uri).unwrap();
+        assert_eq!((query.semester_id, query.week), (3, Some(5)));
+    }
+}
@@ -1,6 +1,10 @@
 use crate::common::error::AppError; // 适配主分支 error 路径
+use super::admin;
+use super::calendar::{self, SectionTimes};
+use super::conflict::{ConflictIndex, Slot};
 use super::entity::*;
-use sqlx::{MySqlPool, Row, types::Json};
+use super::import::{self, CourseCandidate, ImportedRow};
+use sqlx::{MySqlConnection, MySqlPool, Row, types::Json};
 use serde_json;
 
 // ==================== 学期相关 ====================
@@ -30,6 +34,225 @@ pub async fn get_semesters(pool: &MySqlPool) -> Result<Vec<Semester>, AppError>
     Ok(semesters)
 }
 
+/// 获取单个学期
+pub async fn get_semester(pool: &MySqlPool, semester_id: i64) -> Result<Semester, AppError> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, name, start_date, end_date, is_current
+        FROM semesters
+        WHERE id = ?
+        "#
+    )
+        .bind(semester_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("学期不存在".to_string()))?;
+
+    Ok(Semester {
+        id: row.get("id"),
+        name: row.get("name"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        is_current: row.get("is_current"),
+    })
+}
+
+// ==================== 学期管理（管理员） ====================
+
+/// 新建学期；is_current 为 true 时同一事务内取消其他学期的当前标记
+pub async fn create_semester(pool: &MySqlPool, input: SemesterInput) -> Result<Semester, AppError> {
+    admin::validate_semester(&input.name, &input.start_date, &input.end_date)
+        .map_err(AppError::BadRequest)?;
+
+    let mut tx = pool.begin().await?;
+
+    if input.is_current {
+        sqlx::query("UPDATE semesters SET is_current = FALSE WHERE is_current = TRUE")
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO semesters (name, start_date, end_date, is_current)
+        VALUES (?, ?, ?, ?)
+        "#
+    )
+        .bind(input.name.trim())
+        .bind(&input.start_date)
+        .bind(&input.end_date)
+        .bind(input.is_current)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    get_semester(pool, result.last_insert_id() as i64).await
+}
+
+/// 更新学期名称和起止日期
+pub async fn update_semester(
+    pool: &MySqlPool,
+    semester_id: i64,
+    input: UpdateSemesterInput,
+) -> Result<Semester, AppError> {
+    let existing = get_semester(pool, semester_id).await?;
+
+    let name = input.name.unwrap_or(existing.name);
+    let start_date = input.start_date.unwrap_or(existing.start_date);
+    let end_date = input.end_date.unwrap_or(existing.end_date);
+    admin::validate_semester(&name, &start_date, &end_date).map_err(AppError::BadRequest)?;
+
+    let mut tx = pool.begin().await?;
+
+    // 学期缩短后，全校课程的周次不能超出新的周数（个人课表不影响学期修改）
+    sqlx::query("SELECT id FROM semesters WHERE id = ? FOR UPDATE")
+        .bind(semester_id)
+        .execute(&mut *tx)
+        .await?;
+    if let Some(max_week) = admin::semester_weeks(&start_date, &end_date) {
+        let latest = latest_public_course_week(&mut tx, semester_id).await?;
+        if latest > max_week {
+            return Err(AppError::Conflict(format!(
+                "修改后学期只有 {} 周，已有全校课程安排到第 {} 周，请先调整课程周次",
+                max_week, latest
+            )));
+        }
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE semesters
+        SET name = ?, start_date = ?, end_date = ?
+        WHERE id = ?
+        "#
+    )
+        .bind(name.trim())
+        .bind(&start_date)
+        .bind(&end_date)
+        .bind(semester_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    get_semester(pool, semester_id).await
+}
+
+/// 学期内全校课程用到的最大周次（没有课程时为 0）
+async fn latest_public_course_week(conn: &mut MySqlConnection, semester_id: i64) -> Result<i32, AppError> {
+    let rows: Vec<Json<Vec<i32>>> = sqlx::query_scalar("SELECT weeks_range FROM public_courses WHERE semester_id = ?")
+        .bind(semester_id)
+        .fetch_all(conn)
+        .await?;
+
+    Ok(rows.iter().flat_map(|weeks| weeks.0.iter().copied()).max().unwrap_or(0))
+}
+
+/// 删除学期；当前学期或仍有课程 / 课表引用的学期不能删除
+pub async fn delete_semester(pool: &MySqlPool, semester_id: i64) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query("SELECT is_current FROM semesters WHERE id = ? FOR UPDATE")
+        .bind(semester_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("学期不存在".to_string()))?;
+
+    if row.get::<bool, _>("is_current") {
+        return Err(AppError::Conflict("不能删除当前学期，请先设置其他学期为当前学期".to_string()));
+    }
+
+    let referenced: i64 = sqlx::query_scalar(
+        r#"
+        SELECT (SELECT COUNT(*) FROM public_courses WHERE semester_id = ?)
+             + (SELECT COUNT(*) FROM schedule_items WHERE semester_id = ?)
+        "#
+    )
+        .bind(semester_id)
+        .bind(semester_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if referenced > 0 {
+        return Err(AppError::Conflict("学期下还有课程或课表，不能删除".to_string()));
+    }
+
+    sqlx::query("DELETE FROM section_times WHERE semester_id = ?")
+        .bind(semester_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM semesters WHERE id = ?")
+        .bind(semester_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 设置当前学期：同一条 UPDATE 里切换所有学期的标记，保证任何时刻只有一个当前学期
+pub async fn set_current_semester(pool: &MySqlPool, semester_id: i64) -> Result<Semester, AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT id FROM semesters WHERE id = ? FOR UPDATE")
+        .bind(semester_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("学期不存在".to_string()))?;
+
+    sqlx::query("UPDATE semesters SET is_current = (id = ?)")
+        .bind(semester_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    get_semester(pool, semester_id).await
+}
+
+// ==================== 作息表相关 ====================
+
+/// 获取学期在 section_times 表中配置的作息表，未配置时返回 None
+pub async fn get_section_times(
+    pool: &MySqlPool,
+    semester_id: i64,
+) -> Result<Option<SectionTimes>, AppError> {
+    let rows = sqlx::query_as::<_, SectionTimeDb>(
+        r#"
+        SELECT semester_id, section, start_time, end_time
+        FROM section_times
+        WHERE semester_id = ?
+        ORDER BY section
+        "#
+    )
+        .bind(semester_id)
+        .fetch_all(pool)
+        .await?;
+
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    SectionTimes::from_rows(
+        rows.into_iter()
+            .map(|r| (r.section, r.start_time, r.end_time))
+            .collect(),
+    )
+        .map(Some)
+        .map_err(|e| AppError::InternalError(format!("学期 {} 的作息表配置错误: {}", semester_id, e)))
+}
+
+/// 获取学期实际使用的作息表：优先学期配置，否则使用默认作息表
+pub async fn resolve_section_times(
+    pool: &MySqlPool,
+    semester_id: i64,
+    fallback: &SectionTimes,
+) -> Result<SectionTimes, AppError> {
+    Ok(get_section_times(pool, semester_id)
+        .await?
+        .unwrap_or_else(|| fallback.clone()))
+}
+
 // ==================== 全校课程相关 ====================
 
 /// 获取全校课程列表（支持分页和筛选）
@@ -137,14 +360,149 @@ pub async fn get_public_courses(
     Ok((courses, pagination))
 }
 
+/// 批量导入 / 更新全校课程（管理员）
+///
+/// 每门课先按学期作息表和学期周数校验，不合法的放进 failed_items；
+/// 合法的在同一个事务里写入：带 id 的按 id 更新，否则按
+/// （课程名、教师、星期、开始节次、结束节次）匹配已有课程，匹配不到则新增。
+pub async fn upsert_public_courses(
+    pool: &MySqlPool,
+    semester_id: i64,
+    courses: Vec<PublicCourseInput>,
+    parse_failures: Vec<FailedItem>,
+    fallback: &SectionTimes,
+) -> Result<UpsertCoursesResult, AppError> {
+    let semester = get_semester(pool, semester_id).await?;
+    let max_week = admin::semester_weeks(&semester.start_date, &semester.end_date);
+    let sections = resolve_section_times(pool, semester_id, fallback).await?;
+
+    let mut result = UpsertCoursesResult {
+        inserted: 0,
+        updated: 0,
+        failed_items: parse_failures,
+    };
+
+    let mut tx = pool.begin().await?;
+
+    for course in courses {
+        if let Err(msg) = admin::validate_course(&course, &sections, max_week) {
+            result.failed_items.push(FailedItem {
+                course_name: course.course_name,
+                error_message: msg,
+                conflict_with: None,
+            });
+            continue;
+        }
+
+        let existing_id: Option<i64> = match course.id {
+            Some(id) => {
+                let found: Option<i64> = sqlx::query_scalar(
+                    "SELECT id FROM public_courses WHERE id = ? AND semester_id = ?"
+                )
+                    .bind(id)
+                    .bind(semester_id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                if found.is_none() {
+                    result.failed_items.push(FailedItem {
+                        course_name: course.course_name,
+                        error_message: format!("课程 {} 不存在或不属于该学期", id),
+                        conflict_with: None,
+                    });
+                    continue;
+                }
+                found
+            }
+            None => {
+                sqlx::query_scalar(
+                    r#"
+                    SELECT id FROM public_courses
+                    WHERE semester_id = ? AND course_name = ? AND teacher_name = ?
+                      AND day_of_week = ? AND start_section = ? AND end_section = ?
+                    LIMIT 1
+                    "#
+                )
+                    .bind(semester_id)
+                    .bind(&course.course_name)
+                    .bind(&course.teacher_name)
+                    .bind(course.day_of_week)
+                    .bind(course.start_section)
+                    .bind(course.end_section)
+                    .fetch_optional(&mut *tx)
+                    .await?
+            }
+        };
+
+        let weeks_json = serde_json::to_string(&course.weeks_range).unwrap();
+
+        match existing_id {
+            Some(id) => {
+                sqlx::query(
+                    r#"
+                    UPDATE public_courses
+                    SET course_name = ?, teacher_name = ?, teacher_id = ?, location = ?,
+                        day_of_week = ?, start_section = ?, end_section = ?, weeks_range = ?,
+                        type = ?, credits = ?, description = ?
+                    WHERE id = ?
+                    "#
+                )
+                    .bind(&course.course_name)
+                    .bind(&course.teacher_name)
+                    .bind(course.teacher_id)
+                    .bind(&course.location)
+                    .bind(course.day_of_week)
+                    .bind(course.start_section)
+                    .bind(course.end_section)
+                    .bind(&weeks_json)
+                    .bind(&course.r#type)
+                    .bind(course.credits)
+                    .bind(&course.description)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                result.updated += 1;
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO public_courses
+                    (semester_id, course_name, teacher_name, teacher_id, location,
+                     day_of_week, start_section, end_section, weeks_range, type, credits, description)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#
+                )
+                    .bind(semester_id)
+                    .bind(&course.course_name)
+                    .bind(&course.teacher_name)
+                    .bind(course.teacher_id)
+                    .bind(&course.location)
+                    .bind(course.day_of_week)
+                    .bind(course.start_section)
+                    .bind(course.end_section)
+                    .bind(&weeks_json)
+                    .bind(&course.r#type)
+                    .bind(course.credits)
+                    .bind(&course.description)
+                    .execute(&mut *tx)
+                    .await?;
+                result.inserted += 1;
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(result)
+}
+
 // ==================== 用户课表相关 ====================
 
 /// 获取用户课表
 pub async fn get_user_schedule(
     pool: &MySqlPool,
-    user_id: i64,
+    user_id: &str,
     semester_id: i64,
     week: Option<i32>,
+    fallback: &SectionTimes,
 ) -> Result<Vec<ScheduleItem>, AppError> {
     let rows = if let Some(week_num) = week {
         // 筛选指定周
@@ -182,7 +540,7 @@ pub async fn get_user_schedule(
             .await?
     };
 
-    let items = rows.into_iter().map(|row| {
+    let mut items: Vec<ScheduleItem> = rows.into_iter().map(|row| {
         let weeks_json: Json<Vec<i32>> = row.get("weeks_range");
         let weeks_range = weeks_json.0;
 
@@ -201,169 +559,151 @@ pub async fn get_user_schedule(
             description: row.get("description"),
             color_hex: row.get("color_hex"),
             is_custom: row.get("is_custom"),
+            start_time: None,
+            end_time: None,
         }
     }).collect();
 
+    let sections = resolve_section_times(pool, semester_id, fallback).await?;
+    calendar::resolve_times(&mut items, &sections);
+
     Ok(items)
 }
 
-/// 检查时间冲突
-async fn check_time_conflict(
-    pool: &MySqlPool,
-    user_id: i64,
+/// 锁住用户行，串行化同一用户的课表写入，避免并发请求各自通过冲突检测
+async fn lock_user_schedule(conn: &mut MySqlConnection, user_id: &str) -> Result<(), AppError> {
+    sqlx::query("SELECT id FROM users WHERE id = ? FOR UPDATE")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// 加载用户本学期已有课表项，用于冲突检测
+async fn load_semester_slots(
+    conn: &mut MySqlConnection,
+    user_id: &str,
     semester_id: i64,
-    day_of_week: i32,
-    start_section: i32,
-    end_section: i32,
-    weeks: &[i32],
     exclude_item_id: Option<i64>,
-) -> Result<bool, AppError> {
-    let rows = if let Some(item_id) = exclude_item_id {
-        sqlx::query(
-            r#"
-            SELECT id, weeks_range
-            FROM schedule_items
-            WHERE user_id = ?
-              AND semester_id = ?
-              AND day_of_week = ?
-              AND id != ?
-              AND (
-                  (start_section <= ? AND end_section >= ?)
-                  OR (start_section <= ? AND end_section >= ?)
-                  OR (start_section >= ? AND end_section <= ?)
-              )
-            "#
-        )
-            .bind(user_id)
-            .bind(semester_id)
-            .bind(day_of_week)
-            .bind(item_id)
-            .bind(start_section)
-            .bind(start_section)
-            .bind(end_section)
-            .bind(end_section)
-            .bind(start_section)
-            .bind(end_section)
-            .fetch_all(pool)
-            .await?
-    } else {
-        sqlx::query(
-            r#"
-            SELECT id, weeks_range
-            FROM schedule_items
-            WHERE user_id = ?
-              AND semester_id = ?
-              AND day_of_week = ?
-              AND (
-                  (start_section <= ? AND end_section >= ?)
-                  OR (start_section <= ? AND end_section >= ?)
-                  OR (start_section >= ? AND end_section <= ?)
-              )
-            "#
-        )
-            .bind(user_id)
-            .bind(semester_id)
-            .bind(day_of_week)
-            .bind(start_section)
-            .bind(start_section)
-            .bind(end_section)
-            .bind(end_section)
-            .bind(start_section)
-            .bind(end_section)
-            .fetch_all(pool)
-            .await?
-    };
+) -> Result<ConflictIndex, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, course_name, day_of_week, start_section, end_section, weeks_range
+        FROM schedule_items
+        WHERE user_id = ? AND semester_id = ?
+        "#
+    )
+        .bind(user_id)
+        .bind(semester_id)
+        .fetch_all(&mut *conn)
+        .await?;
 
-    // 检查周次是否重叠
-    for row in rows {
-        let weeks_json: Json<Vec<i32>> = row.get("weeks_range");
-        let conflict_weeks = weeks_json.0;
-        if weeks.iter().any(|w| conflict_weeks.contains(w)) {
-            return Ok(true); // 存在冲突
-        }
+    let slots = rows
+        .into_iter()
+        .filter(|row| Some(row.get::<i64, _>("id")) != exclude_item_id)
+        .map(|row| {
+            let weeks_json: Json<Vec<i32>> = row.get("weeks_range");
+            Slot::new(
+                row.get::<String, _>("course_name"),
+                row.get("day_of_week"),
+                row.get("start_section"),
+                row.get("end_section"),
+                &weeks_json.0,
+            )
+        });
+
+    Ok(ConflictIndex::new(slots))
+}
+
+/// 校验单个课表项的业务规则（不含冲突检测）
+fn validate_item(item: &ScheduleItemInput, sections: &SectionTimes, max_week: Option<i32>) -> Result<(), String> {
+    if item.is_custom && item.source_id.is_some() {
+        return Err("自定义课程不能有 source_id".to_string());
+    }
+
+    if !item.is_custom && item.source_id.is_none() {
+        return Err("非自定义课程必须有 source_id".to_string());
     }
 
-    Ok(false) // 无冲突
+    // 验证节次在本学期作息表内
+    sections.validate_span(item.start_section, item.end_section)?;
+
+    if !(1..=7).contains(&item.day_of_week) {
+        return Err("星期几必须在 1-7 之间".to_string());
+    }
+
+    admin::validate_weeks(&item.weeks, max_week)
 }
 
 /// 批量添加课表项
+///
+/// 整批在内存中与用户本学期已有课表、以及同批次中已接受的课表项做冲突检测，
+/// 通过的课表项在同一个事务中插入。
 pub async fn add_schedule_items(
     pool: &MySqlPool,
-    user_id: i64,
+    user_id: &str,
     semester_id: i64,
     items: Vec<ScheduleItemInput>,
+    fallback: &SectionTimes,
 ) -> Result<BatchAddResult, AppError> {
+    save_schedule_items(pool, user_id, semester_id, items, fallback, false).await
+}
+
+/// 校验并保存课表项；dry_run 时只做校验和冲突检测，不写入数据库（返回的 id 为 0）
+async fn save_schedule_items(
+    pool: &MySqlPool,
+    user_id: &str,
+    semester_id: i64,
+    items: Vec<ScheduleItemInput>,
+    fallback: &SectionTimes,
+    dry_run: bool,
+) -> Result<BatchAddResult, AppError> {
+    let sections = resolve_section_times(pool, semester_id, fallback).await?;
+    let semester = get_semester(pool, semester_id).await?;
+    let max_week = admin::semester_weeks(&semester.start_date, &semester.end_date);
     let mut successful_items = Vec::new();
     let mut failed_items = Vec::new();
 
+    let mut tx = pool.begin().await?;
+    lock_user_schedule(&mut tx, user_id).await?;
+    let mut index = load_semester_slots(&mut tx, user_id, semester_id, None).await?;
+
     for item in items {
-        // 验证业务规则
-        if item.is_custom && item.source_id.is_some() {
+        if let Err(msg) = validate_item(&item, &sections, max_week) {
             failed_items.push(FailedItem {
-                course_name: item.course_name.clone(),
-                error_message: "自定义课程不能有 source_id".to_string(),
+                course_name: item.course_name,
+                error_message: msg,
+                conflict_with: None,
             });
             continue;
         }
 
-        if !item.is_custom && item.source_id.is_none() {
+        // 检查时间冲突（包括同批次中已接受的课表项）
+        let slot = Slot::new(
+            item.course_name.clone(),
+            item.day_of_week,
+            item.start_section,
+            item.end_section,
+            &item.weeks,
+        );
+        if let Some(conflict) = index.find_conflict(&slot) {
             failed_items.push(FailedItem {
-                course_name: item.course_name.clone(),
-                error_message: "非自定义课程必须有 source_id".to_string(),
+                error_message: format!("与「{}」时间冲突", conflict.course_name),
+                conflict_with: Some(conflict.course_name.clone()),
+                course_name: item.course_name,
             });
             continue;
         }
+        index.insert(slot);
 
-        // 验证时间范围
-        if item.start_section > item.end_section {
-            failed_items.push(FailedItem {
-                course_name: item.course_name.clone(),
-                error_message: "开始节次不能大于结束节次".to_string(),
-            });
+        if dry_run {
+            successful_items.push(ScheduleItem::from_input(0, item));
             continue;
         }
 
-        if item.day_of_week < 1 || item.day_of_week > 7 {
-            failed_items.push(FailedItem {
-                course_name: item.course_name.clone(),
-                error_message: "星期几必须在 1-7 之间".to_string(),
-            });
-            continue;
-        }
-
-        // 检查时间冲突
-        match check_time_conflict(
-            pool,
-            user_id,
-            semester_id,
-            item.day_of_week,
-            item.start_section,
-            item.end_section,
-            &item.weeks,
-            None,
-        )
-            .await
-        {
-            Ok(true) => {
-                failed_items.push(FailedItem {
-                    course_name: item.course_name.clone(),
-                    error_message: "课程时间冲突".to_string(),
-                });
-                continue;
-            }
-            Err(e) => {
-                failed_items.push(FailedItem {
-                    course_name: item.course_name.clone(),
-                    error_message: format!("检查冲突失败: {}", e),
-                });
-                continue;
-            }
-            Ok(false) => {}
-        }
-
-        // 插入数据库
+        // 插入数据库；数据库错误直接中止整批，事务回滚，不会出现部分写入
         let weeks_json = serde_json::to_string(&item.weeks).unwrap();
-        match sqlx::query(
+        let result = sqlx::query(
             r#"
             INSERT INTO schedule_items
             (user_id, semester_id, source_id, course_name, teacher_name, location,
@@ -387,49 +727,101 @@ pub async fn add_schedule_items(
             .bind(&item.description)
             .bind(&item.color_hex)
             .bind(item.is_custom)
-            .execute(pool)
-            .await
-        {
-            Ok(result) => {
-                successful_items.push(ScheduleItem {
-                    id: result.last_insert_id() as i64,
-                    source_id: item.source_id,
-                    course_name: item.course_name,
-                    teacher_name: item.teacher_name,
-                    location: item.location,
-                    day_of_week: item.day_of_week,
-                    start_section: item.start_section,
-                    end_section: item.end_section,
-                    weeks_range: item.weeks,
-                    r#type: item.r#type,
-                    credits: item.credits,
-                    description: item.description,
-                    color_hex: item.color_hex,
-                    is_custom: item.is_custom,
-                });
-            }
-            Err(e) => {
-                failed_items.push(FailedItem {
-                    course_name: item.course_name,
-                    error_message: format!("数据库插入失败: {}", e),
-                });
-            }
-        }
+            .execute(&mut *tx)
+            .await?;
+
+        successful_items.push(ScheduleItem::from_input(result.last_insert_id() as i64, item));
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
     }
 
+    calendar::resolve_times(&mut successful_items, &sections);
+
     Ok(BatchAddResult {
         successful_items,
         failed_items,
     })
 }
 
+/// 从教务系统导出文件导入课表
+///
+/// 能匹配到全校课程的行设置 source_id，其余作为自定义课程导入；
+/// 文件中解析失败的行也放进 failed_items 一起返回。
+pub async fn import_schedule(
+    pool: &MySqlPool,
+    user_id: &str,
+    semester_id: i64,
+    rows: Vec<ImportedRow>,
+    parse_failures: Vec<FailedItem>,
+    dry_run: bool,
+    fallback: &SectionTimes,
+) -> Result<BatchAddResult, AppError> {
+    let candidates: Vec<CourseCandidate> = sqlx::query(
+        r#"
+        SELECT id, course_name, teacher_name, day_of_week, start_section, end_section
+        FROM public_courses
+        WHERE semester_id = ?
+        "#
+    )
+        .bind(semester_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| CourseCandidate {
+            id: row.get("id"),
+            course_name: row.get("course_name"),
+            teacher_name: row.get("teacher_name"),
+            day_of_week: row.get("day_of_week"),
+            start_section: row.get("start_section"),
+            end_section: row.get("end_section"),
+        })
+        .collect();
+
+    let items = rows
+        .into_iter()
+        .map(|row| {
+            let source_id = import::match_public_course(&candidates, &row);
+            ScheduleItemInput {
+                source_id,
+                color_hex: import::default_color(&row.course_name),
+                course_name: row.course_name,
+                teacher_name: row.teacher_name,
+                location: row.location,
+                day_of_week: row.day_of_week,
+                start_section: row.start_section,
+                end_section: row.end_section,
+                weeks: row.weeks,
+                r#type: row.r#type,
+                credits: row.credits,
+                description: None,
+                is_custom: source_id.is_none(),
+            }
+        })
+        .collect();
+
+    let mut result = save_schedule_items(pool, user_id, semester_id, items, fallback, dry_run).await?;
+    let mut failed_items = parse_failures;
+    failed_items.append(&mut result.failed_items);
+    result.failed_items = failed_items;
+
+    Ok(result)
+}
+
 /// 更新课表项
 pub async fn update_schedule_item(
     pool: &MySqlPool,
-    user_id: i64,
+    user_id: &str,
     item_id: i64,
     input: UpdateScheduleItemInput,
+    fallback: &SectionTimes,
 ) -> Result<ScheduleItem, AppError> {
+    let mut tx = pool.begin().await?;
+    lock_user_schedule(&mut tx, user_id).await?;
+
     // 先查询原有数据
     let row = sqlx::query(
         r#"
@@ -442,7 +834,7 @@ pub async fn update_schedule_item(
     )
         .bind(item_id)
         .bind(user_id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
     let existing = row.ok_or_else(|| AppError::NotFound("课表项不存在".to_string()))?;
@@ -468,30 +860,25 @@ pub async fn update_schedule_item(
     let source_id: Option<i64> = existing.get("source_id");
     let is_custom: bool = existing.get("is_custom");
 
-    // 验证时间范围
-    if start_section > end_section {
-        return Err(AppError::BadRequest("开始节次不能大于结束节次".to_string()));
-    }
+    // 验证节次在本学期作息表内
+    let sections = resolve_section_times(pool, semester_id, fallback).await?;
+    sections
+        .validate_span(start_section, end_section)
+        .map_err(|e| AppError::invalid_field("start_section", e))?;
 
-    if day_of_week < 1 || day_of_week > 7 {
-        return Err(AppError::BadRequest("星期几必须在 1-7 之间".to_string()));
+    if !(1..=7).contains(&day_of_week) {
+        return Err(AppError::invalid_field("day_of_week", "星期几必须在 1-7 之间"));
     }
 
-    // 检查时间冲突（排除当前项）
-    let has_conflict = check_time_conflict(
-        pool,
-        user_id,
-        semester_id,
-        day_of_week,
-        start_section,
-        end_section,
-        &weeks,
-        Some(item_id),
-    )
-        .await?;
+    let semester = get_semester(pool, semester_id).await?;
+    admin::validate_weeks(&weeks, admin::semester_weeks(&semester.start_date, &semester.end_date))
+        .map_err(|e| AppError::invalid_field("weeks", e))?;
 
-    if has_conflict {
-        return Err(AppError::BadRequest("课程时间冲突".to_string()));
+    // 检查时间冲突（排除当前项）
+    let index = load_semester_slots(&mut tx, user_id, semester_id, Some(item_id)).await?;
+    let slot = Slot::new(course_name.clone(), day_of_week, start_section, end_section, &weeks);
+    if let Some(conflict) = index.find_conflict(&slot) {
+        return Err(AppError::Conflict(format!("与「{}」时间冲突", conflict.course_name)));
     }
 
     // 更新数据库
@@ -519,10 +906,12 @@ pub async fn update_schedule_item(
         .bind(&color_hex)
         .bind(item_id)
         .bind(user_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
-    Ok(ScheduleItem {
+    tx.commit().await?;
+
+    let mut item = ScheduleItem {
         id: item_id,
         source_id,
         course_name,
@@ -537,13 +926,18 @@ pub async fn update_schedule_item(
         description,
         color_hex,
         is_custom,
-    })
+        start_time: None,
+        end_time: None,
+    };
+    calendar::resolve_times(std::slice::from_mut(&mut item), &sections);
+
+    Ok(item)
 }
 
 /// 删除课表项
 pub async fn delete_schedule_item(
     pool: &MySqlPool,
-    user_id: i64,
+    user_id: &str,
     item_id: i64,
 ) -> Result<(), AppError> {
     let result = sqlx::query(
@@ -563,3 +957,39 @@ pub async fn delete_schedule_item(
 
     Ok(())
 }
+
+// ==================== 课表订阅相关 ====================
+
+/// 用户订阅链接的当前版本（schedule_feeds 表没有记录时为 0）
+pub async fn get_feed_version(pool: &MySqlPool, user_id: &str) -> Result<i64, AppError> {
+    let version: Option<i64> = sqlx::query_scalar("SELECT version FROM schedule_feeds WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(version.unwrap_or(0))
+}
+
+/// 重置订阅链接：版本加一，之前签发的所有订阅链接随之失效，返回新版本
+pub async fn rotate_feed_version(pool: &MySqlPool, user_id: &str) -> Result<i64, AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO schedule_feeds (user_id, version, updated_at)
+        VALUES (?, 1, NOW())
+        ON DUPLICATE KEY UPDATE version = version + 1, updated_at = NOW()
+        "#
+    )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let version: i64 = sqlx::query_scalar("SELECT version FROM schedule_feeds WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(version)
+}
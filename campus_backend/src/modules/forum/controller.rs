@@ -2,25 +2,22 @@ use axum::{
     extract::{Path, Query, State},
     http::HeaderMap,
     response::IntoResponse,
-    Json, Extension, Router,
-    routing::{get, post, put, delete},
+    Json, Router,
+    routing::{get, post, delete},
 };
 use serde_json::json;
 
-use crate::common::{state::AppState, error::AppError};
+use crate::common::{
+    auth::{Admin, AuthUser, OptionalAuthUser, RequireRole},
+    state::AppState,
+    error::AppError,
+    response::ApiResponse,
+};
 use super::{
     entity::*,
     service::ForumService,
 };
 
-fn success<T: serde::Serialize>(data: T) -> impl IntoResponse {
-    Json(json!({
-        "code": 200,
-        "message": "success",
-        "data": data
-    }))
-}
-
 //
 // =====================================================================
 // Boards
@@ -28,7 +25,7 @@ fn success<T: serde::Serialize>(data: T) -> impl IntoResponse {
 //
 async fn list_boards(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
     let list = ForumService::get_board_list(&state).await?;
-    Ok(success(json!({ "list": list })))
+    Ok(ApiResponse::ok(json!({ "list": list })))
 }
 
 //
@@ -39,59 +36,55 @@ async fn list_boards(State(state): State<AppState>) -> Result<impl IntoResponse,
 async fn create_post(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
     Json(req): Json<CreatePostRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     if !headers.contains_key("Idempotency-Key") {
         return Err(AppError::BadRequest("Missing Idempotency-Key header".into()));
     }
 
-    let post_id = ForumService::create_post(&state, &user_id, req).await?;
-    let post = ForumService::get_post_detail(&state, &post_id, Some(&user_id)).await?;
+    let post_id = ForumService::create_post(&state, &auth_user.user_id, req).await?;
+    let post = ForumService::get_post_detail(&state, &post_id, Some(&auth_user.user_id)).await?;
 
-    Ok(success(post))
+    Ok(ApiResponse::ok(post))
 }
 
 async fn list_posts(
     State(state): State<AppState>,
-    user_ext: Option<Extension<String>>,
+    auth_user: OptionalAuthUser,
     Query(query): Query<PostQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = user_ext.map(|x| x.0);
-
-    let result = ForumService::get_post_list(&state, user_id.as_deref(), query).await?;
-    Ok(success(result))
+    let result = ForumService::get_post_list(&state, auth_user.user_id(), query).await?;
+    Ok(ApiResponse::ok(result))
 }
 
 async fn get_post(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    user_ext: Option<Extension<String>>,
+    auth_user: OptionalAuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = user_ext.map(|x| x.0);
-
-    let result = ForumService::get_post_detail(&state, &id, user_id.as_deref()).await?;
-    Ok(success(result))
+    let result = ForumService::get_post_detail(&state, &id, auth_user.user_id()).await?;
+    Ok(ApiResponse::ok(result))
 }
 
 async fn delete_post(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    ForumService::delete_post(&state, &id, &user_id).await?;
-    Ok(success(serde_json::Value::Null))
+    ForumService::delete_post(&state, &id, &auth_user.user_id).await?;
+    Ok(ApiResponse::empty())
 }
 
 async fn update_post(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
     Json(req): Json<UpdatePostRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    ForumService::update_post(&state, &id, req).await?;
-    let post = ForumService::get_post_detail(&state, &id, Some(&user_id)).await?;
-    Ok(success(post))
+    ForumService::update_post(&state, &id, &auth_user.user_id, req).await?;
+    let post = ForumService::get_post_detail(&state, &id, Some(&auth_user.user_id)).await?;
+    Ok(ApiResponse::ok(post))
 }
 
 //
@@ -102,13 +95,13 @@ async fn update_post(
 async fn like_post(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
     Json(req): Json<LikeActionRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let (count, is_liked) =
-        ForumService::toggle_like_post(&state, &id, &user_id, &req.actions).await?;
+        ForumService::toggle_like_post(&state, &id, &auth_user.user_id, &req.actions).await?;
 
-    Ok(success(json!({
+    Ok(ApiResponse::ok(json!({
         "current_like_count": count,
         "is_liked": is_liked
     })))
@@ -117,13 +110,13 @@ async fn like_post(
 async fn collect_post(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
     Json(req): Json<CollectActionRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let (is_collected, _total) =
-        ForumService::toggle_collect_post(&state, &id, &user_id, &req.action).await?;
+        ForumService::toggle_collect_post(&state, &id, &auth_user.user_id, &req.action).await?;
 
-    Ok(success(json!({ "is_collected": is_collected })))
+    Ok(ApiResponse::ok(json!({ "is_collected": is_collected })))
 }
 
 //
@@ -134,11 +127,11 @@ async fn collect_post(
 async fn create_comment(
     State(state): State<AppState>,
     Path(post_id): Path<String>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
     Json(req): Json<CreateCommentRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let comment = ForumService::create_comment(&state, &post_id, &user_id, req).await?;
-    Ok(success(json!({
+    let comment = ForumService::create_comment(&state, &post_id, &auth_user.user_id, req).await?;
+    Ok(ApiResponse::ok(json!({
         "comment_id": comment.id,
         "comment": comment
     })))
@@ -147,34 +140,32 @@ async fn create_comment(
 async fn list_comments(
     State(state): State<AppState>,
     Path(post_id): Path<String>,
-    user_ext: Option<Extension<String>>,
+    auth_user: OptionalAuthUser,
     Query(query): Query<CommentQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = user_ext.map(|x| x.0);
-
-    let list = ForumService::get_comments(&state, &post_id, user_id.as_deref(), query).await?;
-    Ok(success(list))
+    let list = ForumService::get_comments(&state, &post_id, auth_user.user_id(), query).await?;
+    Ok(ApiResponse::ok(list))
 }
 
 async fn delete_comment(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    ForumService::delete_comment(&state, &id, &user_id).await?;
-    Ok(success(serde_json::Value::Null))
+    ForumService::delete_comment(&state, &id, &auth_user.user_id).await?;
+    Ok(ApiResponse::empty())
 }
 
 async fn like_comment(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
     Json(req): Json<LikeActionRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let (count, is_liked) =
-        ForumService::toggle_like_comment(&state, &id, &user_id, &req.actions).await?;
+        ForumService::toggle_like_comment(&state, &id, &auth_user.user_id, &req.actions).await?;
 
-    Ok(success(json!({
+    Ok(ApiResponse::ok(json!({
         "current_like_count": count,
         "is_liked": is_liked
     })))
@@ -187,30 +178,30 @@ async fn like_comment(
 //
 async fn create_report(
     State(state): State<AppState>,
-    Extension(user_id): Extension<String>,
+    auth_user: AuthUser,
     Json(req): Json<CreateReportRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let id = ForumService::create_report(&state, &user_id, req).await?;
-    Ok(success(json!({ "report_id": id })))
+    let id = ForumService::create_report(&state, &auth_user.user_id, req).await?;
+    Ok(ApiResponse::ok(json!({ "report_id": id })))
 }
 
 async fn admin_list_reports(
     State(state): State<AppState>,
-    Extension(_user_id): Extension<String>,
+    _admin: RequireRole<Admin>,
     Query(query): Query<AdminReportQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let list = ForumService::admin_list_reports(&state, query).await?;
-    Ok(success(list))
+    Ok(ApiResponse::ok(list))
 }
 
 async fn admin_audit_post(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Extension(_user_id): Extension<String>,
+    _admin: RequireRole<Admin>,
     Json(req): Json<AdminPostStatusRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     ForumService::admin_audit_post(&state, &id, req).await?;
-    Ok(success(serde_json::Value::Null))
+    Ok(ApiResponse::empty())
 }
 
 //
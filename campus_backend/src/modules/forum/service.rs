@@ -112,10 +112,24 @@ impl ForumService {
     pub async fn update_post(
         state: &AppState,
         post_id: &str,
+        user_id: &str,
         req: UpdatePostRequest
     ) -> Result<(), AppError> {
         let pool = &state.pool;
 
+        // 0. Only the author can edit
+        let post = sqlx::query!(
+            "SELECT author_id FROM posts WHERE id = ? AND is_deleted = 0",
+            post_id
+        )
+            .fetch_optional(pool)
+            .await?
+            .ok_or(AppError::NotFound("Post not found".into()))?;
+
+        if post.author_id != user_id {
+            return Err(AppError::Forbidden("Not the author".into()));
+        }
+
         let mut qb: QueryBuilder<MySql> =
             QueryBuilder::new("UPDATE posts SET updated_at = NOW() ");
 
@@ -255,7 +269,7 @@ impl ForumService {
         match current_user_id {
             Some(uid) => qb.push_bind(uid),
             None => qb.push("NULL"),
-        }
+        };
 
         qb.push(") as is_liked, ");
         qb.push(
@@ -265,7 +279,7 @@ impl ForumService {
         match current_user_id {
             Some(uid) => qb.push_bind(uid),
             None => qb.push("NULL"),
-        }
+        };
 
         qb.push(") as is_collected ");
 
@@ -760,7 +774,7 @@ impl ForumService {
         match user_id {
             Some(uid) => qb.push_bind(uid),
             None => qb.push("NULL"),
-        }
+        };
 
         qb.push(") as is_liked ");
 
@@ -1,13 +1,13 @@
 use axum::{
-    extract::{Json, State, HeaderMap},
-    http::StatusCode,
+    extract::{Json, State},
     response::IntoResponse,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::json;
 
+use crate::common::auth::{self, AuthUser};
 use crate::common::error::AppError;
-use crate::common::jwt;
+use crate::common::response::ApiResponse;
 use crate::common::state::AppState;
 
 use super::entity::UpdateUserProfile;
@@ -29,54 +29,23 @@ pub struct RegisterRequest {
     pub phone: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ChangePasswordRequest {
     pub old_password: String,
     pub new_password: String,
 }
 
-fn success_response<T: Serialize>(data: T) -> impl IntoResponse {
-    (
-        StatusCode::OK,
-        Json(json!({ "code": 200, "message": "success", "data": data })),
-    )
-}
-
-fn empty_success_response() -> impl IntoResponse {
-    (
-        StatusCode::OK,
-        Json(json!({ "code": 200, "message": "success", "data": null })),
-    )
-}
-
-/// 从 JWT 中解析 user_id
-fn get_current_user_id(headers: &HeaderMap, state: &AppState) -> Result<String, AppError> {
-    let auth_header = headers
-        .get("Authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| AppError::AuthError("缺少Authorization头".to_string()))?;
-
-    if !auth_header.starts_with("Bearer ") {
-        return Err(AppError::AuthError("Authorization头格式错误".into()));
-    }
-
-    let token = &auth_header[7..];
-
-    if state.jwt_config.is_token_expired(token) {
-        return Err(AppError::AuthError("Token已过期".into()));
-    }
-
-    let claims = state.jwt_config.validate_token(token)
-        .map_err(|e| AppError::AuthError(format!("Token验证失败: {}", e)))?;
-
-    Ok(claims.sub)
-}
-
 /// 路由
 pub fn router() -> axum::Router<AppState> {
     axum::Router::new()
         .route("/auth/login", axum::routing::post(login_handler))
         .route("/auth/register", axum::routing::post(register_handler))
+        .route("/auth/refresh", axum::routing::post(refresh_handler))
         .route("/users/me", axum::routing::get(get_user_info_handler))
         .route("/users/me", axum::routing::put(update_profile_handler))
         .route("/auth/logout", axum::routing::post(logout_handler))
@@ -92,11 +61,13 @@ pub async fn login_handler(
 
     let user = UserService::login(pool, &req.student_id, &req.password).await?;
 
-    let token = state.jwt_config.generate_token(&user.id)
-        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    let tokens = auth::issue_token_pair(&state, &user.id, &user.role).await?;
 
     let login_data = json!({
-        "token": token,
+        "token": tokens.access_token,
+        "refresh_token": tokens.refresh_token,
+        "expires_in": tokens.expires_in,
+        "refresh_expires_in": tokens.refresh_expires_in,
         "user": {
             "id": user.id,
             "student_id": user.student_id,
@@ -119,7 +90,22 @@ pub async fn login_handler(
         }
     });
 
-    Ok(success_response(login_data))
+    Ok(ApiResponse::ok(login_data))
+}
+
+/// 刷新 Token（refresh token 轮换，旧的随即失效）
+pub async fn refresh_handler(
+    State(state): State<AppState>,
+    Json(req): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let tokens = auth::refresh_token_pair(&state, &req.refresh_token).await?;
+
+    Ok(ApiResponse::ok(json!({
+        "token": tokens.access_token,
+        "refresh_token": tokens.refresh_token,
+        "expires_in": tokens.expires_in,
+        "refresh_expires_in": tokens.refresh_expires_in,
+    })))
 }
 
 /// 注册
@@ -138,15 +124,15 @@ pub async fn register_handler(
     )
         .await?;
 
-    Ok(success_response(json!({ "user_id": user.id })))
+    Ok(ApiResponse::ok(json!({ "user_id": user.id })))
 }
 
 /// 获取个人信息
 pub async fn get_user_info_handler(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    auth_user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let uid = get_current_user_id(&headers, &state)?;
+    let uid = auth_user.user_id;
 
     let user = UserService::get_user_info(&state.pool, &uid).await?;
 
@@ -171,44 +157,50 @@ pub async fn get_user_info_handler(
         "setting_notification_switch": user.setting_notification_switch,
     });
 
-    Ok(success_response(resp))
+    Ok(ApiResponse::ok(resp))
 }
 
 /// 更新个人资料
 pub async fn update_profile_handler(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    auth_user: AuthUser,
     Json(update_data): Json<UpdateUserProfile>,
 ) -> Result<impl IntoResponse, AppError> {
-    let uid = get_current_user_id(&headers, &state)?;
+    let uid = auth_user.user_id;
 
     UserService::update_profile(&state.pool, &uid, update_data).await?;
 
-    Ok(empty_success_response())
+    Ok(ApiResponse::empty())
 }
 
 /// 退出
 pub async fn logout_handler(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    auth_user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let uid = get_current_user_id(&headers, &state)?;
+    let family_expires_at = chrono::Utc::now().timestamp() + state.jwt_config.refresh_expiration;
 
-    UserService::logout(&state.pool, &uid).await?;
+    UserService::logout(state.revocation.as_ref(), &auth_user, family_expires_at).await?;
 
-    Ok(empty_success_response())
+    Ok(ApiResponse::empty())
 }
 
 /// 修改密码
 pub async fn change_password_handler(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    auth_user: AuthUser,
     Json(req): Json<ChangePasswordRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let uid = get_current_user_id(&headers, &state)?;
+    let uid = auth_user.user_id;
 
-    UserService::change_password(&state.pool, &uid, &req.old_password, &req.new_password)
-        .await?;
+    UserService::change_password(
+        &state.pool,
+        state.revocation.as_ref(),
+        &uid,
+        &req.old_password,
+        &req.new_password,
+    )
+    .await?;
 
-    Ok(empty_success_response())
+    Ok(ApiResponse::empty())
 }
@@ -84,7 +84,7 @@ impl User {
             phone,
             email,
             avatar_url: String::new(),
-            role: String::new(),
+            role: "student".to_string(),
             wechat_id: String::new(),
             collection_count: 0,
             forum_activity_score: 0,
@@ -105,9 +105,26 @@ impl User {
         self.last_login_at = Some(Utc::now());
     }
 
-    /// （弱）密码比较（注：实际项目中不可用）
-    pub fn check_password(&self, password: &str) -> bool {
-        self.password == password
+    /// 校验密码（兼容历史遗留的明文密码行）
+    /// bcrypt 校验是 CPU 密集操作，放到阻塞线程池里执行，不占用异步工作线程
+    pub async fn check_password(&self, password: &str) -> bool {
+        if self.has_legacy_password() {
+            return self.password == password;
+        }
+        let (password, hash) = (password.to_string(), self.password.clone());
+        tokio::task::spawn_blocking(move || bcrypt::verify(password, &hash).unwrap_or(false))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// 数据库里存的是否还是旧版明文密码
+    /// bcrypt 哈希固定 60 个字符，并以 `$2a$`/`$2b$`/`$2x$`/`$2y$` 开头
+    pub fn has_legacy_password(&self) -> bool {
+        let is_bcrypt = self.password.len() == 60
+            && ["$2a$", "$2b$", "$2x$", "$2y$"]
+                .iter()
+                .any(|prefix| self.password.starts_with(prefix));
+        !is_bcrypt
     }
 }
 
@@ -125,3 +142,41 @@ pub struct UpdateUserProfile {
     pub setting_privacy_course: Option<String>,
     pub setting_notification_switch: Option<bool>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_with_password(password: &str) -> User {
+        User::new(
+            "20230001".into(),
+            "test".into(),
+            password.into(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_check_hashed_password() {
+        let hash = bcrypt::hash("secret123", 4).unwrap();
+        let user = user_with_password(&hash);
+
+        assert!(!user.has_legacy_password());
+        assert!(user.check_password("secret123").await);
+        assert!(!user.check_password("wrong").await);
+    }
+
+    #[tokio::test]
+    async fn test_check_legacy_plaintext_password() {
+        let user = user_with_password("secret123");
+
+        assert!(user.has_legacy_password());
+        assert!(user.check_password("secret123").await);
+        assert!(!user.check_password("wrong").await);
+    }
+}
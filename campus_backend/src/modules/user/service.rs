@@ -1,4 +1,6 @@
+use crate::common::auth::AuthUser;
 use crate::common::error::AppError;
+use crate::common::revocation::RevocationStore;
 use sqlx::MySqlPool;
 use chrono::Utc;
 
@@ -6,6 +8,15 @@ use super::entity::{User, UpdateUserProfile};
 
 pub struct UserService;
 
+/// 使用 bcrypt 对密码做哈希（在阻塞线程池里执行，不占用异步工作线程）
+async fn hash_password(password: &str) -> Result<String, AppError> {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+        .await
+        .map_err(|e| AppError::InternalError(format!("密码哈希失败: {}", e)))?
+        .map_err(|e| AppError::InternalError(format!("密码哈希失败: {}", e)))
+}
+
 impl UserService {
     /// 用户登录
     pub async fn login(
@@ -25,11 +36,24 @@ impl UserService {
             .bind(student_id)
             .fetch_optional(pool)
             .await?
-            .ok_or_else(|| AppError::AuthError("用户不存在".to_string()))?;
+            .ok_or_else(|| AppError::Unauthorized("用户不存在".to_string()))?;
 
         // 验证密码
-        if !user.check_password(password) {
-            return Err(AppError::AuthError("密码错误".to_string()));
+        if !user.check_password(password).await {
+            return Err(AppError::Unauthorized("密码错误".to_string()));
+        }
+
+        // 旧版明文密码：登录成功后就地升级为 bcrypt 哈希
+        if user.has_legacy_password() {
+            user.password = hash_password(password).await?;
+
+            sqlx::query(
+                r#"UPDATE users SET password = ? WHERE id = ?"#
+            )
+                .bind(&user.password)
+                .bind(&user.id)
+                .execute(pool)
+                .await?;
         }
 
         // 更新最后登录时间
@@ -65,11 +89,11 @@ impl UserService {
             .await?;
 
         if existing.is_some() {
-            return Err(AppError::BadRequest("学号已存在".to_string()));
+            return Err(AppError::Conflict("学号已存在".to_string()));
         }
 
-        // 创建新用户（密码应该哈希，这里简单处理）
-        let password_hash = password;
+        // 创建新用户
+        let password_hash = hash_password(&password).await?;
 
         let new_user = User::new(
             student_id,
@@ -118,7 +142,14 @@ impl UserService {
             .bind(&new_user.grade)
             .bind(&new_user.weekly_course_count)
             .execute(pool)
-            .await?;
+            .await
+            .map_err(|e| match e {
+                // 并发注册同一学号时，两个请求都可能通过上面的检查，由唯一索引兜底
+                sqlx::Error::Database(db) if db.is_unique_violation() => {
+                    AppError::Conflict("学号已存在".to_string())
+                }
+                e => AppError::DatabaseError(e),
+            })?;
 
         Ok(new_user)
     }
@@ -210,9 +241,10 @@ impl UserService {
         Ok(user)
     }
 
-    /// 修改密码
+    /// 修改密码：成功后吊销该用户所有登录会话，旧密码下签发的 Token 全部失效
     pub async fn change_password(
         pool: &MySqlPool,
+        revocation: &dyn RevocationStore,
         user_id: &str,
         old_password: &str,
         new_password: &str,
@@ -220,12 +252,12 @@ impl UserService {
         let user = Self::get_user_info(pool, user_id).await?;
 
         // 验证密码
-        if !user.check_password(old_password) {
-            return Err(AppError::AuthError("旧密码错误".to_string()));
+        if !user.check_password(old_password).await {
+            return Err(AppError::Unauthorized("旧密码错误".to_string()));
         }
 
-        // 更新密码（未哈希）
-        let new_password_hash = new_password.to_string();
+        // 更新密码
+        let new_password_hash = hash_password(new_password).await?;
 
         sqlx::query(
             r#"UPDATE users SET password = ?, updated_at = ? WHERE id = ?"#
@@ -236,12 +268,18 @@ impl UserService {
             .execute(pool)
             .await?;
 
-        Ok(())
+        revocation.revoke_user_families(user_id).await
     }
 
-    /// 退出登录
-    pub async fn logout(_pool: &MySqlPool, _user_id: &str) -> Result<(), AppError> {
-        Ok(())
+    /// 退出登录：把当前 Token 的 jti 加入吊销列表，直到其自然过期；
+    /// 同时吊销它所在的家族，让对应的 refresh token 也一并失效
+    pub async fn logout(
+        revocation: &dyn RevocationStore,
+        auth_user: &AuthUser,
+        family_expires_at: i64,
+    ) -> Result<(), AppError> {
+        revocation.revoke(&auth_user.jti, auth_user.exp).await?;
+        revocation.revoke_family(&auth_user.family, family_expires_at).await
     }
 }
     
\ No newline at end of file
@@ -1,4 +0,0 @@
-pub mod user;
-mod course;
-mod bbs;
-pub mod activity;
\ No newline at end of file